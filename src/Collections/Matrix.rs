@@ -1,10 +1,248 @@
+use std::ops::{Add, Index, IndexMut, Mul, Sub};
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct Matrix<T>{
-    values: Vec<Vec<T>>   
+    values: Vec<Vec<T>>
 }
 
 
 impl<T> Matrix<T> {
-   pub fn new() -> Self{
-    todo!()
-   }
-}
\ No newline at end of file
+    pub fn new() -> Self{
+        Matrix { values: Vec::new() }
+    }
+
+    /// Builds a matrix from its rows, which must all have the same length.
+    pub fn from_rows(values: Vec<Vec<T>>) -> Self {
+        debug_assert!(values.windows(2).all(|w| w[0].len() == w[1].len()), "Rows should have the same length");
+        Matrix { values }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.values.first().map_or(0, |r| r.len())
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.values[i]
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows() == self.cols()
+    }
+}
+
+impl<T> Default for Matrix<T> {
+    fn default() -> Self {
+        Matrix::new()
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        &self.values[i][j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        &mut self.values[i][j]
+    }
+}
+
+impl Matrix<f64> {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Matrix { values: vec![vec![0.0; cols]; rows] }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut m = Matrix::zeros(n, n);
+        for i in 0..n {
+            m[(i, i)] = 1.0;
+        }
+        m
+    }
+
+    pub fn diagonal(values: &[f64]) -> Self {
+        let mut m = Matrix::zeros(values.len(), values.len());
+        for (i, v) in values.iter().enumerate() {
+            m[(i, i)] = *v;
+        }
+        m
+    }
+
+    pub fn scale(&self, factor: f64) -> Self {
+        Matrix {
+            values: self.values.iter().map(|r| r.iter().map(|v| v * factor).collect()).collect(),
+        }
+    }
+
+    /// Row vector times matrix.
+    pub fn left_mul(&self, v: &[f64]) -> Vec<f64> {
+        debug_assert!(v.len() == self.rows(), "Dimension mismatch");
+        (0..self.cols())
+            .map(|j| v.iter().enumerate().map(|(i, x)| x * self.values[i][j]).sum())
+            .collect()
+    }
+
+    /// Matrix times column vector.
+    pub fn right_mul(&self, v: &[f64]) -> Vec<f64> {
+        debug_assert!(v.len() == self.cols(), "Dimension mismatch");
+        self.values
+            .iter()
+            .map(|r| r.iter().zip(v).map(|(a, b)| a * b).sum())
+            .collect()
+    }
+
+    /// Sums of the rows, i.e. the matrix times a column of ones.
+    pub fn row_sums(&self) -> Vec<f64> {
+        self.values.iter().map(|r| r.iter().sum()).collect()
+    }
+
+    /// Largest absolute row sum.
+    pub fn norm_inf(&self) -> f64 {
+        self.values
+            .iter()
+            .map(|r| r.iter().map(|v| v.abs()).sum::<f64>())
+            .fold(0.0, f64::max)
+    }
+
+    pub fn pow(&self, k: u32) -> Self {
+        debug_assert!(self.is_square(), "Power of a non square matrix");
+        let mut result = Matrix::identity(self.rows());
+        for _ in 0..k {
+            result = &result * self;
+        }
+        result
+    }
+
+    /// Gauss-Jordan inversion with partial pivoting, `None` if the matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        debug_assert!(self.is_square(), "Inverse of a non square matrix");
+        let n = self.rows();
+        let mut a = self.clone();
+        let mut inv = Matrix::identity(n);
+        let tolerance = f64::EPSILON * self.norm_inf();
+        for col in 0..n {
+            let pivot = (col..n)
+                .max_by(|x, y| a[(*x, col)].abs().partial_cmp(&a[(*y, col)].abs()).unwrap())
+                .unwrap();
+            if a[(pivot, col)].abs() <= tolerance {
+                return None;
+            }
+            a.values.swap(col, pivot);
+            inv.values.swap(col, pivot);
+            let p = a[(col, col)];
+            for j in 0..n {
+                a[(col, j)] /= p;
+                inv[(col, j)] /= p;
+            }
+            for row in 0..n {
+                if row != col {
+                    let f = a[(row, col)];
+                    if f != 0.0 {
+                        for j in 0..n {
+                            a[(row, j)] -= f * a[(col, j)];
+                            inv[(row, j)] -= f * inv[(col, j)];
+                        }
+                    }
+                }
+            }
+        }
+        Some(inv)
+    }
+
+    /// Solves x A = b for the row vector x, `None` if A is singular.
+    pub fn solve_left(&self, b: &[f64]) -> Option<Vec<f64>> {
+        self.inverse().map(|inv| inv.left_mul(b))
+    }
+
+    /// Matrix exponential by scaling and squaring of a Taylor expansion.
+    pub fn exp(&self) -> Self {
+        debug_assert!(self.is_square(), "Exponential of a non square matrix");
+        let norm = self.norm_inf();
+        let squarings = if norm > 0.5 { (norm / 0.5).log2().ceil() as u32 } else { 0 };
+        let a = self.scale(1.0 / 2f64.powi(squarings as i32));
+        let mut result = Matrix::identity(self.rows());
+        let mut term = Matrix::identity(self.rows());
+        for k in 1..=20 {
+            term = (&term * &a).scale(1.0 / k as f64);
+            result = &result + &term;
+        }
+        for _ in 0..squarings {
+            result = &result * &result;
+        }
+        result
+    }
+}
+
+impl Mul for &Matrix<f64> {
+    type Output = Matrix<f64>;
+
+    fn mul(self, rhs: Self) -> Matrix<f64> {
+        debug_assert!(self.cols() == rhs.rows(), "Dimension mismatch");
+        let mut m = Matrix::zeros(self.rows(), rhs.cols());
+        for i in 0..self.rows() {
+            for k in 0..self.cols() {
+                let a = self.values[i][k];
+                if a != 0.0 {
+                    for j in 0..rhs.cols() {
+                        m.values[i][j] += a * rhs.values[k][j];
+                    }
+                }
+            }
+        }
+        m
+    }
+}
+
+impl Add for &Matrix<f64> {
+    type Output = Matrix<f64>;
+
+    fn add(self, rhs: Self) -> Matrix<f64> {
+        debug_assert!(self.rows() == rhs.rows() && self.cols() == rhs.cols(), "Dimension mismatch");
+        Matrix {
+            values: self
+                .values
+                .iter()
+                .zip(&rhs.values)
+                .map(|(a, b)| a.iter().zip(b).map(|(x, y)| x + y).collect())
+                .collect(),
+        }
+    }
+}
+
+impl Sub for &Matrix<f64> {
+    type Output = Matrix<f64>;
+
+    fn sub(self, rhs: Self) -> Matrix<f64> {
+        self + &rhs.scale(-1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inverse_and_exp() {
+        let a = Matrix::from_rows(vec![vec![4.0, 7.0], vec![2.0, 6.0]]);
+        let inv = a.inverse().unwrap();
+        let id = &a * &inv;
+        for i in 0..2 {
+            for j in 0..2 {
+                assert!((id[(i, j)] - if i == j { 1.0 } else { 0.0 }).abs() < 1e-12);
+            }
+        }
+        assert!(Matrix::from_rows(vec![vec![1.0, 2.0], vec![2.0, 4.0]]).inverse().is_none());
+
+        let d = Matrix::diagonal(&[-1.0, 2.0]).exp();
+        assert!((d[(0, 0)] - (-1.0f64).exp()).abs() < 1e-12);
+        assert!((d[(1, 1)] - 2.0f64.exp()).abs() < 1e-10);
+        assert_eq!(d[(0, 1)], 0.0);
+    }
+}
@@ -0,0 +1,246 @@
+/* --------------------------------------------------------------------------
+ * Method of batch means: the observations of a single long run are grouped
+ * in consecutive batches of equal size, whose means are treated as i.i.d.
+ * once the batches are long enough for the lag-1 autocorrelation
+ *
+ *      r1 = sum (m(i) - m)(m(i+1) - m) / sum (m(i) - m)^2
+ *
+ * of the batch means to be negligible. An adaptive estimator keeps between
+ * b and 2b batches: whenever 2b are complete and |r1| is above the
+ * threshold, adjacent batches are merged, doubling the batch size.
+ * --------------------------------------------------------------------------
+ */
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    Events::Event,
+    Stations::{Station::IEventManager, StationData::StationData},
+};
+
+use super::Statistics::ConfidenceInterval;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchMeans {
+    batch_size: usize,
+    sum: f64,
+    count: usize,
+    means: Vec<f64>,
+    adaptive: Option<(usize, f64)>,
+}
+
+impl BatchMeans {
+    /// Batches of `batch_size` observations.
+    pub fn new(batch_size: usize) -> Self {
+        debug_assert!(batch_size > 0, "Use a positive batch size");
+        BatchMeans {
+            batch_size,
+            sum: 0.0,
+            count: 0,
+            means: Vec::new(),
+            adaptive: None,
+        }
+    }
+
+    /// Starts from `batch_size` and doubles it while 2 `batches` batches are complete
+    /// and the lag-1 autocorrelation of their means exceeds `threshold` in absolute value.
+    pub fn adaptive(batch_size: usize, batches: usize, threshold: f64) -> Self {
+        debug_assert!(batches >= 2, "Use at least 2 batches");
+        BatchMeans {
+            adaptive: Some((batches, threshold)),
+            ..BatchMeans::new(batch_size)
+        }
+    }
+
+    pub fn add(&mut self, x: f64) {
+        self.sum += x;
+        self.count += 1;
+        if self.count == self.batch_size {
+            self.means.push(self.sum / self.batch_size as f64);
+            self.sum = 0.0;
+            self.count = 0;
+            if let Some((batches, threshold)) = self.adaptive {
+                let n = self.means.len();
+                if n >= 2 * batches && n % 2 == 0 && self.autocorrelation().abs() > threshold {
+                    self.rebatch();
+                }
+            }
+        }
+    }
+
+    /// Merges adjacent complete batches, doubling the batch size. An odd last batch
+    /// goes back to the partial one.
+    pub fn rebatch(&mut self) {
+        if self.means.len() % 2 != 0 {
+            let last = self.means.pop().unwrap();
+            self.sum += last * self.batch_size as f64;
+            self.count += self.batch_size;
+        }
+        self.means = self.means.chunks(2).map(|pair| 0.5 * (pair[0] + pair[1])).collect();
+        self.batch_size *= 2;
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Means of the complete batches.
+    pub fn means(&self) -> &[f64] {
+        &self.means
+    }
+
+    /// Observations in complete batches.
+    pub fn observations(&self) -> usize {
+        self.means.len() * self.batch_size
+    }
+
+    /// Grand mean of the complete batches.
+    pub fn mean(&self) -> f64 {
+        self.means.iter().sum::<f64>() / self.means.len() as f64
+    }
+
+    /// Lag-1 autocorrelation of the batch means.
+    pub fn autocorrelation(&self) -> f64 {
+        let n = self.means.len();
+        if n < 3 {
+            return f64::NAN;
+        }
+        let mean = self.mean();
+        let variance: f64 = self.means.iter().map(|m| (m - mean) * (m - mean)).sum();
+        if variance == 0.0 {
+            return 0.0;
+        }
+        let covariance: f64 = self.means.windows(2).map(|w| (w[0] - mean) * (w[1] - mean)).sum();
+        covariance / variance
+    }
+
+    /// Student t interval of the steady-state mean with one degree of freedom per batch less one.
+    pub fn interval(&self, confidence: f64) -> ConfidenceInterval {
+        let n = self.means.len();
+        let mean = self.mean();
+        let std_dev = if n > 1 {
+            (self.means.iter().map(|m| (m - mean) * (m - mean)).sum::<f64>() / (n - 1) as f64).sqrt()
+        } else {
+            f64::NAN
+        };
+        ConfidenceInterval::student(mean, std_dev / (n as f64).sqrt(), n.saturating_sub(1), confidence)
+    }
+}
+
+/// Quantity observed when a job leaves a station.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Observed {
+    ResponseTime,
+    WaitingTime,
+    ServiceTime,
+    /// Jobs left behind by the departure, distributed as the population seen by
+    /// arrivals (the time-average one under Poisson arrivals).
+    Population,
+}
+
+/// Departure handler feeding a shared batch means estimator, then forwarding the
+/// event to the next handler if any.
+pub struct BatchMeansObserver {
+    observed: Observed,
+    estimator: Rc<RefCell<BatchMeans>>,
+    next: Option<Box<dyn IEventManager>>,
+}
+
+impl BatchMeansObserver {
+    pub fn new(observed: Observed, estimator: Rc<RefCell<BatchMeans>>) -> Self {
+        BatchMeansObserver {
+            observed,
+            estimator,
+            next: None,
+        }
+    }
+
+    /// Hands every departure to `next` once observed, e.g. a routing handler.
+    pub fn forward(mut self, next: Box<dyn IEventManager>) -> Self {
+        self.next = Some(next);
+        self
+    }
+}
+
+impl IEventManager for BatchMeansObserver {
+    fn process_event(&mut self, event: &Event, data: &mut StationData) {
+        let x = match self.observed {
+            Observed::ResponseTime => data.clock - event.arrivalTime,
+            Observed::WaitingTime => event.createTime - event.arrivalTime,
+            Observed::ServiceTime => data.clock - event.createTime,
+            Observed::Population => (data.sysClients - 1) as f64,
+        };
+        self.estimator.borrow_mut().add(x);
+        if let Some(next) = &mut self.next {
+            next.process_event(event, data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Engines::Engine,
+        Random::{
+            rngs::RandomGenerator,
+            Distributions::{Distribution, Normal},
+            VarianceReduction::StreamPlan,
+        },
+        TestSupport::mm1_server,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_batch_means() {
+        let mut batches = BatchMeans::new(2);
+        for x in 1..=9 {
+            batches.add(x as f64);
+        }
+        assert_eq!(batches.means(), &[1.5, 3.5, 5.5, 7.5]);
+        batches.add(10.0);
+        batches.add(11.0);
+        batches.rebatch();
+        assert_eq!(batches.batch_size(), 4);
+        assert_eq!(batches.means(), &[2.5, 6.5]);
+        assert_eq!(batches.observations(), 8);
+        batches.add(12.0);
+        assert_eq!(batches.means(), &[2.5, 6.5, 10.5]);
+
+        // AR(1) process of mean 0 strongly correlated at lag 1
+        let mut generator = RandomGenerator::with_seed(2024);
+        let noise = Normal::new(0.0, 1.0);
+        let mut plain = BatchMeans::new(1);
+        let mut adaptive = BatchMeans::adaptive(1, 20, 0.1);
+        let mut x = 0.0;
+        for _ in 0..200000 {
+            x = 0.9 * x + noise.sample(&mut generator);
+            plain.add(x);
+            adaptive.add(x);
+        }
+        assert!(plain.autocorrelation() > 0.85);
+        assert!(adaptive.batch_size() >= 64, "batch size {}", adaptive.batch_size());
+        assert!(adaptive.autocorrelation().abs() < plain.autocorrelation());
+        let interval = adaptive.interval(0.99);
+        assert!(interval.contains(0.0), "{:?}", interval);
+        // correlated observations make the naive interval far too narrow
+        assert!(plain.interval(0.99).half_width < 0.5 * interval.half_width);
+    }
+
+    #[test]
+    fn test_station_feed() {
+        let mut engine = Engine::new();
+        let estimator = Rc::new(RefCell::new(BatchMeans::adaptive(16, 16, 0.2)));
+        let mut station = mm1_server(&mut engine, &mut StreamPlan::new(140), 0.5);
+        station.set_departure_handler(Box::new(BatchMeansObserver::new(Observed::ResponseTime, estimator.clone())));
+        engine.register_station(Box::new(station));
+        engine.run_until(50000.0);
+
+        // M/M/1 with utilization 0.5: mean response time 1
+        let estimator = estimator.borrow();
+        let interval = estimator.interval(0.99);
+        assert!(estimator.observations() > 40000);
+        assert!((interval.mean - 1.0).abs() < 0.1, "{:?}", interval);
+        assert!(interval.half_width < 0.15);
+    }
+}
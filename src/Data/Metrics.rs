@@ -0,0 +1,126 @@
+use crate::Stations::StationData::StationData;
+
+/// Derived metrics of one station, see the methods of `StationData`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StationMetrics {
+    pub name: String,
+    pub utilization: f64,
+    pub throughput: f64,
+    pub mean_population: f64,
+    pub mean_queue_length: f64,
+    pub mean_response_time: f64,
+    pub mean_wait: f64,
+}
+
+impl StationMetrics {
+    pub fn new(name: &str, data: &StationData) -> Self {
+        StationMetrics {
+            name: name.to_string(),
+            utilization: data.utilization(),
+            throughput: data.throughput(),
+            mean_population: data.mean_population(),
+            mean_queue_length: data.mean_queue_length(),
+            mean_response_time: data.mean_response_time(),
+            mean_wait: data.mean_wait(),
+        }
+    }
+}
+
+/// Metrics of every station of an engine and their totals.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SystemSummary {
+    pub stations: Vec<StationMetrics>,
+    /// Mean number of jobs in all the stations.
+    pub mean_population: f64,
+    pub mean_queue_length: f64,
+}
+
+impl SystemSummary {
+    pub fn new(stations: Vec<StationMetrics>) -> Self {
+        SystemSummary {
+            mean_population: stations.iter().map(|s| s.mean_population).sum(),
+            mean_queue_length: stations.iter().map(|s| s.mean_queue_length).sum(),
+            stations,
+        }
+    }
+
+    pub fn station(&self, name: &str) -> Option<&StationMetrics> {
+        self.stations.iter().find(|s| s.name == name)
+    }
+
+    /// Station of highest utilization.
+    pub fn bottleneck(&self) -> Option<&StationMetrics> {
+        self.stations
+            .iter()
+            .max_by(|a, b| a.utilization.partial_cmp(&b.utilization).unwrap())
+    }
+
+    /// Mean time a job spends in the system by Little's law, given the system throughput:
+    /// the external arrival rate of an open network, or the throughput of the reference
+    /// station of a closed one.
+    pub fn mean_response_time(&self, throughput: f64) -> f64 {
+        if throughput > 0.0 {
+            self.mean_population / throughput
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Engines::Engine,
+        Random::Distributions::Exponential,
+        Sources::{ArrivalProcess::RenewalArrivals, SourceRuler::SourcePolicyManager},
+        Stations::{FCFSRuler::FCFSPolicyManager, Routing::RoutingMatrix, Station::Station},
+    };
+
+    #[test]
+    fn test_tandem_summary() {
+        // Poisson arrivals of rate 1 to exponential servers of means 0.5 then 0.25
+        let mut engine = Engine::new();
+        let arrivals = RenewalArrivals::new(Exponential::new(1.0), 206);
+        let mut source = SourcePolicyManager::new("Source", arrivals, "A", 0);
+        source.use_engine(&mut engine as *mut Engine);
+        source.start(0.0);
+        let mut station = Station::new("Source");
+        station.set_handler(Box::new(source));
+        engine.register_station(Box::new(station));
+        let mut routing = RoutingMatrix::new();
+        routing.set(("A", 0), ("B", 0), 1.0).unwrap();
+        for (name, mean, stream) in [("A", 0.5, 207), ("B", 0.25, 208)] {
+            let mut server = FCFSPolicyManager::new();
+            server.use_engine(&mut engine as *mut Engine);
+            server.set_class_distribution(0, Exponential::new(mean), stream);
+            let mut departures = routing.handler(name, 209);
+            departures.use_engine(&mut engine as *mut Engine);
+            let mut station = Station::new(name);
+            station.set_handler(Box::new(server));
+            station.set_departure_handler(Box::new(departures));
+            engine.register_station(Box::new(station));
+        }
+        engine.run_until(20000.0);
+        engine.advance(20000.0);
+
+        let summary = engine.summary();
+        assert_eq!(summary.stations.len(), 3);
+        assert_eq!(summary.station("Source").unwrap().mean_population, 0.0);
+        let a = summary.station("A").unwrap();
+        let b = summary.station("B").unwrap();
+        assert!((a.utilization - 0.5).abs() < 0.02, "{:?}", a);
+        assert!((b.utilization - 0.25).abs() < 0.02, "{:?}", b);
+        assert!((a.throughput - 1.0).abs() < 0.03 && (b.throughput - 1.0).abs() < 0.03);
+        assert!((a.mean_population - 1.0).abs() < 0.15, "{:?}", a);
+        assert!((b.mean_population - 1.0 / 3.0).abs() < 0.05, "{:?}", b);
+        assert!((a.mean_wait - 0.5).abs() < 0.1, "{:?}", a);
+        // Little's law against the tally of the jobs that left
+        let data = engine.station("A").unwrap().get_data();
+        assert!((a.mean_response_time / data.responseTimes.mean() - 1.0).abs() < 0.01);
+        assert!((data.mean_queue_length() - data.mean_population() + data.utilization()).abs() < 1e-9);
+        assert_eq!(summary.bottleneck().unwrap().name, "A");
+        assert!((summary.mean_population - a.mean_population - b.mean_population).abs() < 1e-12);
+        let response = summary.mean_response_time(data.arrival_rate());
+        assert!((response - 4.0 / 3.0).abs() < 0.15, "system response {}", response);
+    }
+}
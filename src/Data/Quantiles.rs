@@ -0,0 +1,272 @@
+/* --------------------------------------------------------------------------
+ * Streaming quantile estimators, in constant or logarithmic memory.
+ *
+ * P2         Jain & Chlamtac's P-square algorithm: five markers track the
+ *            minimum, p/2, p, (1 + p)/2 quantiles and the maximum, moved
+ *            by piecewise parabolic interpolation. One quantile per
+ *            estimator, not mergeable.
+ * Histogram  logarithmic buckets (gamma^(i-1), gamma^i] with
+ *            gamma = (1 + a) / (1 - a), so that every quantile is returned
+ *            within relative error a (as in DDSketch). Histograms of the
+ *            same accuracy merge by adding their counts, e.g. across
+ *            replications.
+ * --------------------------------------------------------------------------
+ */
+
+use std::collections::BTreeMap;
+
+/// P-square estimator of the `p` quantile.
+#[derive(Clone, Debug, PartialEq)]
+pub struct P2Quantile {
+    p: f64,
+    count: usize,
+    heights: [f64; 5],
+    positions: [f64; 5],
+    desired: [f64; 5],
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    pub fn new(p: f64) -> Self {
+        debug_assert!(0.0 < p && p < 1.0, "Use 0.0 < p < 1.0");
+        P2Quantile {
+            p,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increments: [0.0, 0.5 * p, p, 0.5 * (1.0 + p), 1.0],
+        }
+    }
+
+    pub fn add(&mut self, x: f64) {
+        if self.count < 5 {
+            self.heights[self.count] = x;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            }
+            return;
+        }
+        self.count += 1;
+        let q = &mut self.heights;
+        let k = if x < q[0] {
+            q[0] = x;
+            0
+        } else if x >= q[4] {
+            q[4] = x;
+            3
+        } else {
+            (1..5).position(|i| x < q[i]).unwrap()
+        };
+        for position in &mut self.positions[k + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(&self.increments) {
+            *desired += increment;
+        }
+        for i in 1..4 {
+            let n = &mut self.positions;
+            let d = self.desired[i] - n[i];
+            if (d >= 1.0 && n[i + 1] - n[i] > 1.0) || (d <= -1.0 && n[i - 1] - n[i] < -1.0) {
+                let d = d.signum();
+                let parabolic = q[i]
+                    + d / (n[i + 1] - n[i - 1])
+                        * ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                            + (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]));
+                q[i] = if q[i - 1] < parabolic && parabolic < q[i + 1] {
+                    parabolic
+                } else {
+                    let j = (i as f64 + d) as usize;
+                    q[i] + d * (q[j] - q[i]) / (n[j] - n[i])
+                };
+                n[i] += d;
+            }
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn p(&self) -> f64 {
+        self.p
+    }
+
+    /// Estimate of the quantile, exact (nearest rank) below five observations and NaN without any.
+    pub fn quantile(&self) -> f64 {
+        match self.count {
+            0 => f64::NAN,
+            1..=4 => {
+                let mut values = self.heights[..self.count].to_vec();
+                values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                values[((self.p * self.count as f64).ceil() as usize).clamp(1, self.count) - 1]
+            }
+            _ => self.heights[2],
+        }
+    }
+}
+
+/// Mergeable histogram of non-negative values answering quantiles within a relative accuracy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    gamma: f64,
+    zeros: u64,
+    buckets: BTreeMap<i32, u64>,
+    count: u64,
+    min: f64,
+    max: f64,
+}
+
+impl Histogram {
+    /// Quantiles within relative error `accuracy`, e.g. 0.01 for 1%.
+    pub fn new(accuracy: f64) -> Self {
+        debug_assert!(0.0 < accuracy && accuracy < 1.0, "Use 0.0 < accuracy < 1.0");
+        Histogram::with_gamma((1.0 + accuracy) / (1.0 - accuracy))
+    }
+
+    /// Buckets growing by the factor `gamma` = (1 + accuracy) / (1 - accuracy), for
+    /// constant contexts.
+    pub(crate) const fn with_gamma(gamma: f64) -> Self {
+        Histogram {
+            gamma,
+            zeros: 0,
+            buckets: BTreeMap::new(),
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        (self.gamma - 1.0) / (self.gamma + 1.0)
+    }
+
+    pub fn add(&mut self, x: f64) {
+        debug_assert!(x >= 0.0, "Histogram values should be non-negative");
+        if x > 0.0 {
+            let index = (x.ln() / self.gamma.ln()).ceil() as i32;
+            *self.buckets.entry(index).or_insert(0) += 1;
+        } else {
+            self.zeros += 1;
+        }
+        self.count += 1;
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+    }
+
+    /// Adds the counts of `other`, which must have the same accuracy.
+    pub fn merge(&mut self, other: &Histogram) {
+        debug_assert!(self.gamma == other.gamma, "Cannot merge histograms of different accuracy");
+        for (index, count) in &other.buckets {
+            *self.buckets.entry(*index).or_insert(0) += count;
+        }
+        self.zeros += other.zeros;
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// Estimate of the `q` quantile, NaN without observations.
+    pub fn quantile(&self, q: f64) -> f64 {
+        debug_assert!((0.0..=1.0).contains(&q), "Use 0.0 <= q <= 1.0");
+        if self.count == 0 {
+            return f64::NAN;
+        }
+        let rank = (q * (self.count - 1) as f64).floor() as u64;
+        if rank < self.zeros {
+            return 0.0;
+        }
+        let mut seen = self.zeros;
+        for (index, count) in &self.buckets {
+            seen += count;
+            if seen > rank {
+                let value = 2.0 * self.gamma.powi(*index) / (self.gamma + 1.0);
+                return value.clamp(self.min, self.max);
+            }
+        }
+        self.max
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Data::Replications::Replications,
+        Random::{
+            rngs::RandomGenerator,
+            Distributions::{Distribution, Exponential},
+        },
+        Stations::StationData::{StationData, QUANTILE_ACCURACY},
+        TestSupport::mm1,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_quantiles() {
+        let mut generator = RandomGenerator::with_seed(8080);
+        let exponential = Exponential::new(1.0);
+        let ps = [0.5, 0.95, 0.99];
+        let mut p2: Vec<P2Quantile> = ps.iter().map(|p| P2Quantile::new(*p)).collect();
+        let (mut first, mut second) = (Histogram::new(0.01), Histogram::new(0.01));
+        let mut whole = Histogram::new(0.01);
+        for i in 0..100000 {
+            let x = exponential.sample(&mut generator);
+            p2.iter_mut().for_each(|e| e.add(x));
+            if i % 2 == 0 { first.add(x) } else { second.add(x) }
+            whole.add(x);
+        }
+        first.merge(&second);
+        assert_eq!(first, whole);
+        for (estimator, p) in p2.iter().zip(ps) {
+            let exact = -(1.0 - p).ln();
+            assert!((estimator.quantile() / exact - 1.0).abs() < 0.03, "p2 {} {}", p, estimator.quantile());
+            let estimate = whole.quantile(p);
+            assert!((estimate / exact - 1.0).abs() < 0.03, "histogram {} {}", p, estimate);
+        }
+        assert!((whole.quantile(1.0) / whole.max() - 1.0).abs() <= whole.accuracy());
+        assert_eq!(whole.count(), 100000);
+
+        let mut small = P2Quantile::new(0.5);
+        assert!(small.quantile().is_nan());
+        [3.0, 1.0, 2.0].iter().for_each(|x| small.add(*x));
+        assert_eq!(small.quantile(), 2.0);
+        let mut zeros = Histogram::new(0.05);
+        [0.0, 0.0, 0.0, 10.0].iter().for_each(|x| zeros.add(*x));
+        assert_eq!(zeros.quantile(0.5), 0.0);
+        assert!((zeros.quantile(1.0) - 10.0).abs() < 0.5);
+        // the constant constructor of the stations matches the accuracy
+        assert_eq!(StationData::new().responseQuantiles, Histogram::new(QUANTILE_ACCURACY));
+    }
+
+    #[test]
+    fn test_station_quantiles() {
+        // M/M/1 response times are exponential of mean 1: p95 = ln 20
+        let replications = Replications::new(4, 5000.0).streams(210, 2);
+        let mut merged = Histogram::new(0.01);
+        let mut count = 0;
+        for r in 0..4 {
+            let engine = replications.replicate(r, &mut mm1);
+            let data = engine.station("Server").unwrap().get_data();
+            assert_eq!(data.responseQuantiles.count(), data.completions as u64);
+            count += data.completions as u64;
+            merged.merge(&data.responseQuantiles);
+        }
+        assert_eq!(merged.count(), count);
+        let p95 = merged.quantile(0.95);
+        assert!((p95 / 20f64.ln() - 1.0).abs() < 0.1, "p95 {}", p95);
+    }
+}
@@ -0,0 +1,253 @@
+/* --------------------------------------------------------------------------
+ * Independent replications: every replication builds its model on a fresh
+ * Engine, draws from streams of its own (replication r gets the streams
+ * first + r * per_replication onward) and runs up to the same horizon.
+ * Before the model is built the streams of the replication are restarted
+ * where seeding the global source with the base seed starts them (see
+ * UniformSource::restart), so replication r sees the same numbers whatever
+ * ran before it, on any generator.
+ * Each metric yields one observation per replication, summarized by its
+ * mean, standard deviation and Student t confidence interval.
+ * --------------------------------------------------------------------------
+ */
+
+use crate::{
+    Engines::Engine,
+    Random::{rngs::with_global_source, VarianceReduction::StreamPlan},
+    Stations::StationData::StationData,
+};
+
+use super::Statistics::Summary;
+
+/// Value computed from the data of `station` at the end of a replication.
+pub struct Metric {
+    pub name: String,
+    pub station: String,
+    value: Box<dyn Fn(&StationData) -> f64>,
+}
+
+impl Metric {
+    pub fn new(name: &str, station: &str, value: impl Fn(&StationData) -> f64 + 'static) -> Self {
+        Metric {
+            name: name.to_string(),
+            station: station.to_string(),
+            value: Box::new(value),
+        }
+    }
+
+    pub fn observe(&self, engine: &Engine) -> f64 {
+        match engine.station(&self.station) {
+            Some(station) => (self.value)(station.get_data()),
+            None => panic!("Not found station {}", self.station),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricReport {
+    pub name: String,
+    pub values: Vec<f64>,
+    pub summary: Summary,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplicationReport {
+    pub metrics: Vec<MetricReport>,
+}
+
+impl ReplicationReport {
+    pub fn get(&self, name: &str) -> Option<&MetricReport> {
+        self.metrics.iter().find(|m| m.name == name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Replications {
+    count: usize,
+    horizon: f64,
+    confidence: f64,
+    first_stream: usize,
+    per_replication: usize,
+    seed: u64,
+}
+
+impl Replications {
+    /// `count` replications of length `horizon`, 95% intervals, 8 streams per
+    /// replication from stream 0, base seed 123456789.
+    pub fn new(count: usize, horizon: f64) -> Self {
+        debug_assert!(count >= 2, "Use at least 2 replications");
+        Replications {
+            count,
+            horizon,
+            confidence: 0.95,
+            first_stream: 0,
+            per_replication: 8,
+            seed: 123456789,
+        }
+    }
+
+    pub fn confidence(mut self, confidence: f64) -> Self {
+        debug_assert!(0.0 < confidence && confidence < 1.0, "Use 0.0 < confidence < 1.0");
+        self.confidence = confidence;
+        self
+    }
+
+    /// Streams handed out to replication r start at `first + r * per_replication`.
+    pub fn streams(mut self, first: usize, per_replication: usize) -> Self {
+        self.first_stream = first;
+        self.per_replication = per_replication;
+        self
+    }
+
+    /// Base seed the streams are restarted from, see `UniformSource::restart`.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Number of replications, the most run by a sequential procedure.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn horizon(&self) -> f64 {
+        self.horizon
+    }
+
+    /// Stream plan of replication `r`.
+    pub fn plan(&self, r: usize) -> StreamPlan {
+        StreamPlan::new(self.first_stream + r * self.per_replication)
+    }
+
+    /// Runs one replication, `build` registering the model on the engine with
+    /// streams taken from the plan, and returns the engine with the statistics
+    /// of every station brought up to the horizon.
+    pub fn replicate(&self, r: usize, build: &mut impl FnMut(&mut Engine, &mut StreamPlan)) -> Box<Engine> {
+        let first = self.first_stream + r * self.per_replication;
+        with_global_source(|global| {
+            assert!(
+                first + self.per_replication <= global.streams(),
+                "Not enough streams for replication {}",
+                r
+            );
+            for stream in first..first + self.per_replication {
+                global.restart(stream, self.seed);
+            }
+        });
+        // boxed so the pointers handed to the stations stay valid when it is returned
+        let mut engine = Box::new(Engine::new());
+        let mut plan = self.plan(r);
+        build(&mut engine, &mut plan);
+        assert!(
+            plan.span() <= self.per_replication,
+            "Replication {} uses more than {} streams",
+            r,
+            self.per_replication
+        );
+        engine.run_until(self.horizon);
+        engine.advance(self.horizon);
+        engine
+    }
+
+    pub fn run(&self, mut build: impl FnMut(&mut Engine, &mut StreamPlan), metrics: &[Metric]) -> ReplicationReport {
+        let mut values = vec![Vec::with_capacity(self.count); metrics.len()];
+        for r in 0..self.count {
+            let engine = self.replicate(r, &mut build);
+            for (metric, values) in metrics.iter().zip(values.iter_mut()) {
+                values.push(metric.observe(&engine));
+            }
+        }
+        ReplicationReport {
+            metrics: metrics
+                .iter()
+                .zip(values)
+                .map(|(metric, values)| MetricReport {
+                    name: metric.name.clone(),
+                    summary: Summary::new(&values, self.confidence),
+                    values,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Random::{
+            rngs::{use_global_source, UniformSource},
+            Mrg32k3a::Mrg32k3a,
+            Pcg64::Pcg64,
+            Xoshiro256::Xoshiro256,
+        },
+        TestSupport::mm1,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_replications() {
+        let replications = Replications::new(20, 2000.0).confidence(0.99).streams(100, 2);
+        let metrics = [
+            Metric::new("utilization", "Server", |d| d.busyTime / d.clock),
+            Metric::new("population", "Server", |d| d.areaN / d.clock),
+        ];
+        let report = replications.run(mm1, &metrics);
+        let utilization = report.get("utilization").unwrap();
+        assert_eq!(utilization.values.len(), 20);
+        assert!(utilization.summary.std_dev > 0.0);
+        // distinct streams give distinct replications
+        assert_ne!(utilization.values[0], utilization.values[1]);
+        assert!((utilization.summary.mean - 0.5).abs() < 0.03, "{:?}", utilization.summary);
+        let population = report.get("population").unwrap();
+        assert!((population.summary.mean - 1.0).abs() < 0.2, "{:?}", population.summary);
+        assert!(population.summary.interval.half_width < 0.2);
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let replications = Replications::new(4, 500.0).streams(120, 2).seed(7);
+        let metrics = [Metric::new("completions", "Server", |d| d.completions as f64)];
+        let first = replications.run(mm1, &metrics);
+        // draws made in between do not shift the streams of the replications
+        with_global_source(|global| global.stream(121).Random());
+        assert_eq!(replications.run(mm1, &metrics), first);
+        let engine = replications.replicate(2, &mut mm1);
+        assert_eq!(engine.station("Server").unwrap().get_data().completions as f64, first.metrics[0].values[2]);
+        let reseeded = replications.seed(8).run(mm1, &metrics);
+        assert_ne!(reseeded.metrics[0].values, first.metrics[0].values);
+    }
+
+    #[test]
+    fn test_any_generator() {
+        let replications = Replications::new(3, 200.0).streams(10, 2).seed(99);
+        let metrics = [Metric::new("completions", "Server", |d| d.completions as f64)];
+        let sources: [fn() -> Box<dyn UniformSource>; 3] =
+            [|| Box::new(Mrg32k3a::new()), || Box::new(Pcg64::new(1)), || Box::new(Xoshiro256::new(1))];
+        for source in sources {
+            use_global_source(source());
+            let report = replications.run(mm1, &metrics);
+            assert!(report.metrics[0].values.iter().all(|c| *c > 0.0));
+            // a source already drawn from restarts the streams alike
+            use_global_source(source());
+            with_global_source(|global| global.stream(11).Random());
+            let engine = replications.replicate(1, &mut mm1);
+            let completions = engine.station("Server").unwrap().get_data().completions as f64;
+            assert_eq!(completions, report.metrics[0].values[1]);
+        }
+    }
+
+    #[test]
+    fn test_statistics_at_horizon() {
+        let engine = Replications::new(2, 123.4).streams(130, 2).replicate(0, &mut mm1);
+        let data = engine.station("Server").unwrap().get_data();
+        assert_eq!(data.clock, 123.4);
+        assert!(data.busyTime <= data.clock);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough streams")]
+    fn test_stream_capacity() {
+        Replications::new(2, 10.0).streams(250, 4).replicate(1, &mut mm1);
+    }
+}
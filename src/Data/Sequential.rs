@@ -0,0 +1,180 @@
+/* --------------------------------------------------------------------------
+ * Sequential stopping: replications (or batches of a single run) are added
+ * until the confidence interval of the mean satisfies
+ *
+ *      half width <= relative * |mean|
+ *
+ * after at least a minimum number of observations, or until the simulated
+ * time would exceed a cap. Stopping on the data biases the interval a
+ * little, use a minimum of 10 or more.
+ * --------------------------------------------------------------------------
+ */
+
+use std::cell::RefCell;
+
+use crate::{Engines::Engine, Random::VarianceReduction::StreamPlan};
+
+use super::{
+    BatchMeans::BatchMeans,
+    Replications::{Metric, Replications},
+    Statistics::{ConfidenceInterval, Summary},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Precision {
+    pub relative: f64,
+    pub confidence: f64,
+    pub minimum: usize,
+    pub max_time: f64,
+}
+
+impl Precision {
+    /// Half width within `relative` of the mean at level `confidence`, after at least
+    /// 10 observations and with no cap on the simulated time.
+    pub fn new(relative: f64, confidence: f64) -> Self {
+        debug_assert!(relative > 0.0, "Use a positive relative precision");
+        debug_assert!(0.0 < confidence && confidence < 1.0, "Use 0.0 < confidence < 1.0");
+        Precision {
+            relative,
+            confidence,
+            minimum: 10,
+            max_time: f64::INFINITY,
+        }
+    }
+
+    pub fn minimum(mut self, minimum: usize) -> Self {
+        debug_assert!(minimum >= 2, "Use a minimum of at least 2");
+        self.minimum = minimum;
+        self
+    }
+
+    /// Cap on the total simulated time.
+    pub fn max_time(mut self, max_time: f64) -> Self {
+        self.max_time = max_time;
+        self
+    }
+
+    pub fn satisfied(&self, interval: &ConfidenceInterval, observations: usize) -> bool {
+        observations >= self.minimum && interval.half_width <= self.relative * interval.mean.abs()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SequentialReport {
+    pub interval: ConfidenceInterval,
+    /// Replications or batch means the interval is built on.
+    pub observations: usize,
+    pub simulated: f64,
+    /// False when the time cap or the number of replications ran out first.
+    pub converged: bool,
+}
+
+/// Runs replications of `build` until `metric` meets `precision`, at most `replications.count()`.
+pub fn replicate_until(
+    replications: &Replications,
+    precision: &Precision,
+    mut build: impl FnMut(&mut Engine, &mut StreamPlan),
+    metric: &Metric,
+) -> SequentialReport {
+    let mut values = Vec::new();
+    let mut simulated = 0.0;
+    loop {
+        let engine = replications.replicate(values.len(), &mut build);
+        values.push(metric.observe(&engine));
+        simulated += replications.horizon();
+        let interval = Summary::new(&values, precision.confidence).interval;
+        let converged = precision.satisfied(&interval, values.len());
+        if converged
+            || values.len() == replications.count()
+            || simulated + replications.horizon() > precision.max_time
+        {
+            return SequentialReport {
+                interval,
+                observations: values.len(),
+                simulated,
+                converged,
+            };
+        }
+    }
+}
+
+/// Runs `engine` in steps of `step` until the batch means fed by its stations meet
+/// `precision`, or `precision.max_time` has been simulated from the current clock.
+pub fn run_until_precise(
+    engine: &mut Engine,
+    estimator: &RefCell<BatchMeans>,
+    precision: &Precision,
+    step: f64,
+) -> SequentialReport {
+    let start = engine.clock();
+    let end = start + precision.max_time;
+    loop {
+        let horizon = (engine.clock() + step).min(end);
+        engine.run_until(horizon);
+        let estimator = estimator.borrow();
+        let observations = estimator.means().len();
+        let interval = estimator.interval(precision.confidence);
+        let converged = observations > 1 && precision.satisfied(&interval, observations);
+        if converged || horizon >= end {
+            return SequentialReport {
+                interval,
+                observations,
+                simulated: engine.clock() - start,
+                converged,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::{
+        Data::BatchMeans::{BatchMeansObserver, Observed},
+        TestSupport::{mm1, mm1_server},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_replicate_until() {
+        let replications = Replications::new(30, 1000.0).streams(144, 2);
+        let metric = Metric::new("response", "Server", |d| d.classes[0].mean_response_time());
+        let build = mm1;
+        let report = replicate_until(&replications, &Precision::new(0.05, 0.95), build, &metric);
+        assert!(report.converged);
+        assert!((10..30).contains(&report.observations), "{:?}", report);
+        assert!(report.interval.relative_precision() <= 0.05);
+        assert_eq!(report.simulated, 1000.0 * report.observations as f64);
+        // replications start empty, the estimate is slightly biased downwards
+        assert!((report.interval.mean - 1.0).abs() < 0.1, "{:?}", report);
+
+        let capped = Precision::new(0.001, 0.95).max_time(12500.0);
+        let report = replicate_until(&replications, &capped, build, &metric);
+        assert!(!report.converged);
+        assert_eq!(report.observations, 12);
+    }
+
+    #[test]
+    fn test_run_until_precise() {
+        let mut engine = Engine::new();
+        let estimator = Rc::new(RefCell::new(BatchMeans::adaptive(16, 16, 0.2)));
+        let mut station = mm1_server(&mut engine, &mut StreamPlan::new(204), 0.5);
+        station.set_departure_handler(Box::new(BatchMeansObserver::new(Observed::ResponseTime, estimator.clone())));
+        engine.register_station(Box::new(station));
+
+        let precision = Precision::new(0.05, 0.95).minimum(16).max_time(200000.0);
+        let report = run_until_precise(&mut engine, &estimator, &precision, 1000.0);
+        assert!(report.converged, "{:?}", report);
+        assert!(report.interval.relative_precision() <= 0.05);
+        assert!(report.simulated < 200000.0 && report.simulated == engine.clock());
+        assert!((report.interval.mean - 1.0).abs() < 0.1, "{:?}", report);
+
+        // the cap counts from the clock of the warmed-up engine
+        let capped = Precision::new(1e-4, 0.95).max_time(3000.0);
+        let report = run_until_precise(&mut engine, &estimator, &capped, 1000.0);
+        assert!(!report.converged);
+        assert_eq!(report.simulated, 3000.0);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::Random::rvms::idfStudent;
+
+/// Two sided interval `mean` +- `half_width` at level `confidence`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConfidenceInterval {
+    pub mean: f64,
+    pub half_width: f64,
+    pub confidence: f64,
+}
+
+impl ConfidenceInterval {
+    /// Student t interval of a mean estimated with standard error `std_error` on `df` degrees of freedom.
+    pub fn student(mean: f64, std_error: f64, df: usize, confidence: f64) -> Self {
+        debug_assert!(0.0 < confidence && confidence < 1.0, "Use 0.0 < confidence < 1.0");
+        let half_width = if df > 0 {
+            idfStudent(df as u32, 0.5 * (1.0 + confidence)) * std_error
+        } else {
+            f64::INFINITY
+        };
+        ConfidenceInterval {
+            mean,
+            half_width,
+            confidence,
+        }
+    }
+
+    pub fn lower(&self) -> f64 {
+        self.mean - self.half_width
+    }
+
+    pub fn upper(&self) -> f64 {
+        self.mean + self.half_width
+    }
+
+    pub fn contains(&self, x: f64) -> bool {
+        self.lower() <= x && x <= self.upper()
+    }
+
+    /// Half width over the absolute mean.
+    pub fn relative_precision(&self) -> f64 {
+        self.half_width / self.mean.abs()
+    }
+}
+
+/// Sample mean, standard deviation and Student t interval of i.i.d. observations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub interval: ConfidenceInterval,
+}
+
+impl Summary {
+    pub fn new(values: &[f64], confidence: f64) -> Self {
+        let count = values.len();
+        let n = count as f64;
+        let mean = if count > 0 { values.iter().sum::<f64>() / n } else { f64::NAN };
+        let std_dev = if count > 1 {
+            (values.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1.0)).sqrt()
+        } else {
+            f64::NAN
+        };
+        Summary {
+            count,
+            mean,
+            std_dev,
+            interval: ConfidenceInterval::student(mean, std_dev / n.sqrt(), count.saturating_sub(1), confidence),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let summary = Summary::new(&[1.0, 2.0, 3.0, 4.0, 5.0], 0.95);
+        assert_eq!(summary.count, 5);
+        assert!((summary.mean - 3.0).abs() < 1e-12);
+        assert!((summary.std_dev - 2.5f64.sqrt()).abs() < 1e-12);
+        // t(4, 0.975) = 2.776
+        let half_width = 2.776445 * (2.5f64 / 5.0).sqrt();
+        assert!((summary.interval.half_width - half_width).abs() < 1e-4);
+        assert!(summary.interval.contains(1.1) && !summary.interval.contains(0.9));
+
+        let single = Summary::new(&[7.0], 0.95);
+        assert!(single.std_dev.is_nan());
+        assert!(single.interval.half_width.is_nan() || single.interval.half_width.is_infinite());
+    }
+}
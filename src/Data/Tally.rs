@@ -0,0 +1,124 @@
+use super::Statistics::ConfidenceInterval;
+
+/// Count, mean, variance (Welford's one pass update), minimum and maximum of
+/// individual observations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tally {
+    count: usize,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Tally {
+    pub const fn new() -> Self {
+        Tally {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn add(&mut self, x: f64) {
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+    }
+
+    /// Combines the observations of two tallies (Chan et al.'s pairwise update).
+    pub fn merge(&mut self, other: &Tally) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * (self.count * other.count) as f64 / count as f64;
+        self.count = count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Zero without observations.
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Sample variance, zero with less than two observations.
+    pub fn variance(&self) -> f64 {
+        if self.count > 1 {
+            self.m2 / (self.count - 1) as f64
+        } else {
+            0.0
+        }
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Infinite without observations.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Minus infinite without observations.
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// Student t interval of the mean, valid for independent observations only.
+    pub fn interval(&self, confidence: f64) -> ConfidenceInterval {
+        let std_error = (self.variance() / self.count as f64).sqrt();
+        ConfidenceInterval::student(self.mean, std_error, self.count.saturating_sub(1), confidence)
+    }
+}
+
+impl Default for Tally {
+    fn default() -> Self {
+        Tally::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tally() {
+        let values = [4.0, 7.0, 13.0, 16.0, 1e9 + 4.0, 1e9 + 7.0];
+        let mut tally = Tally::new();
+        for x in &values[..4] {
+            tally.add(*x);
+        }
+        assert_eq!(tally.count(), 4);
+        assert_eq!(tally.mean(), 10.0);
+        assert_eq!(tally.variance(), 30.0);
+        assert_eq!((tally.min(), tally.max()), (4.0, 16.0));
+
+        let mut other = Tally::new();
+        other.add(values[4]);
+        other.add(values[5]);
+        assert_eq!(other.variance(), 4.5);
+        let mut merged = tally;
+        merged.merge(&other);
+        let mut all = Tally::new();
+        values.iter().for_each(|x| all.add(*x));
+        assert_eq!(merged.count(), 6);
+        assert!((merged.mean() - all.mean()).abs() < 1e-6);
+        assert!((merged.variance() / all.variance() - 1.0).abs() < 1e-12);
+        assert_eq!((merged.min(), merged.max()), (4.0, 1e9 + 7.0));
+        merged.merge(&Tally::new());
+        assert_eq!(merged.count(), 6);
+    }
+}
@@ -0,0 +1,175 @@
+/* --------------------------------------------------------------------------
+ * Initial transient detection on output series, observations in order of
+ * time (e.g. averages over consecutive windows, see interval_averages).
+ *
+ * Welch      the series of several replications are averaged point by
+ *            point, then smoothed by a moving average of half width w
+ *            (shorter near the start); the truncation point is where the
+ *            smoothed curve settles.
+ * MSER-m     the series is grouped in batches of m, and the truncation
+ *            d minimizing  sum_{j > d} (z(j) - mean_d)^2 / (k - d)^2
+ *            over the first half of the k batch means z is chosen.
+ * --------------------------------------------------------------------------
+ */
+
+use crate::{Engines::Engine, Stations::StationData::StationData};
+
+/// Point by point average of the series of several replications, truncated to the shortest.
+pub fn ensemble_average(series: &[Vec<f64>]) -> Vec<f64> {
+    let n = series.iter().map(|s| s.len()).min().unwrap_or(0);
+    (0..n)
+        .map(|i| series.iter().map(|s| s[i]).sum::<f64>() / series.len() as f64)
+        .collect()
+}
+
+/// Welch's moving average of half width `window` of the ensemble average of `series`.
+pub fn welch(series: &[Vec<f64>], window: usize) -> Vec<f64> {
+    let average = ensemble_average(series);
+    let n = average.len().saturating_sub(window);
+    (0..n)
+        .map(|i| {
+            let w = i.min(window);
+            average[i - w..=i + w].iter().sum::<f64>() / (2 * w + 1) as f64
+        })
+        .collect()
+}
+
+/// First point after which Welch's curve stays within `tolerance` (relative) of the
+/// mean of its second half.
+pub fn welch_truncation(series: &[Vec<f64>], window: usize, tolerance: f64) -> usize {
+    let smoothed = welch(series, window);
+    let tail = &smoothed[smoothed.len() / 2..];
+    let level = tail.iter().sum::<f64>() / tail.len() as f64;
+    let band = tolerance * level.abs();
+    smoothed
+        .iter()
+        .rposition(|x| (x - level).abs() > band)
+        .map_or(0, |i| i + 1)
+}
+
+/// MSER truncation point, in observations, over batches of `batch` observations.
+pub fn mser(series: &[f64], batch: usize) -> usize {
+    let means: Vec<f64> = series
+        .chunks_exact(batch)
+        .map(|c| c.iter().sum::<f64>() / batch as f64)
+        .collect();
+    let k = means.len();
+    // suffix sums give the statistic of every truncation in one pass
+    let (mut sum, mut squares) = (0.0, 0.0);
+    let mut best = (f64::INFINITY, 0);
+    for d in (0..k).rev() {
+        sum += means[d];
+        squares += means[d] * means[d];
+        let m = (k - d) as f64;
+        let statistic = (squares - sum * sum / m) / (m * m);
+        if d <= k / 2 && statistic <= best.0 {
+            best = (statistic, d);
+        }
+    }
+    best.1 * batch
+}
+
+/// MSER-5 truncation point, in observations.
+pub fn mser5(series: &[f64]) -> usize {
+    mser(series, 5)
+}
+
+/// Runs the engine over `count` windows of length `interval` from its clock and returns the average
+/// rate of growth of `accumulated` over each window, e.g. the mean population with
+/// `|d| d.areaN` or the throughput with `|d| d.completions as f64`.
+pub fn interval_averages(
+    engine: &mut Engine,
+    station: &str,
+    interval: f64,
+    count: usize,
+    accumulated: impl Fn(&StationData) -> f64,
+) -> Vec<f64> {
+    let observe = |engine: &Engine| match engine.station(station) {
+        Some(station) => accumulated(station.get_data()),
+        None => panic!("Not found station {}", station),
+    };
+    let mut time = engine.clock();
+    engine.advance(time);
+    let mut previous = observe(engine);
+    (0..count)
+        .map(|_| {
+            time += interval;
+            engine.run_until(time);
+            engine.advance(time);
+            let current = observe(engine);
+            let average = (current - previous) / interval;
+            previous = current;
+            average
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Random::{
+            rngs::RandomGenerator,
+            Distributions::{Distribution, Normal},
+            VarianceReduction::StreamPlan,
+        },
+        TestSupport::mm1,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_truncation() {
+        // exponentially decaying bias of time constant 50 over a level of 1
+        let mut generator = RandomGenerator::with_seed(555);
+        let noise = Normal::new(0.0, 0.2);
+        let series: Vec<Vec<f64>> = (0..5)
+            .map(|_| {
+                (0..2000)
+                    .map(|t| 1.0 + 5.0 * (-(t as f64) / 50.0).exp() + noise.sample(&mut generator))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(welch(&series, 10).len(), 1990);
+        let truncation = welch_truncation(&series, 10, 0.1);
+        assert!((100..400).contains(&truncation), "welch {}", truncation);
+        let truncation = mser5(&series[0]);
+        assert!((100..600).contains(&truncation), "mser-5 {}", truncation);
+        // no bias, nothing to delete
+        let flat: Vec<f64> = (0..2000).map(|_| 1.0 + noise.sample(&mut generator)).collect();
+        assert!(mser5(&flat) < 500);
+    }
+
+    #[test]
+    fn test_warmup_deletion() {
+        let mut engine = Engine::new();
+        mm1(&mut engine, &mut StreamPlan::new(142));
+
+        let population = interval_averages(&mut engine, "Server", 10.0, 100, |d| d.areaN);
+        assert_eq!(population.len(), 100);
+        let data = engine.station("Server").unwrap().get_data();
+        assert_eq!(data.clock, 1000.0);
+        assert!((data.areaN / data.clock - population.iter().sum::<f64>() / 100.0).abs() < 1e-9);
+
+        let in_system = data.sysClients;
+        engine.reset_statistics(1000.0);
+        engine.run_until(6000.0);
+        engine.advance(6000.0);
+        let data = engine.station("Server").unwrap().get_data();
+        assert_eq!(data.resetTime, 1000.0);
+        assert_eq!(data.elapsed(), 5000.0);
+        assert_eq!(data.arrivals - data.completions, data.sysClients - in_system);
+        let utilization = data.busyTime / data.elapsed();
+        assert!((utilization - 0.5).abs() < 0.05, "utilization {}", utilization);
+        assert!((data.classes[0].busyTime - data.busyTime).abs() < 1.0);
+
+        // windows start at the engine clock, not at the last event of the station
+        engine.run_until(6005.0);
+        let data = engine.station("Server").unwrap().get_data();
+        assert!(data.clock < 6005.0);
+        let area = data.areaN + data.sysClients as f64 * (6005.0 - data.clock);
+        let population = interval_averages(&mut engine, "Server", 10.0, 3, |d| d.areaN);
+        let data = engine.station("Server").unwrap().get_data();
+        assert_eq!(data.clock, 6035.0);
+        assert!((population.iter().sum::<f64>() * 10.0 - (data.areaN - area)).abs() < 1e-6);
+    }
+}
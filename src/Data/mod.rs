@@ -0,0 +1,8 @@
+pub mod Statistics;
+pub mod Tally;
+pub mod Quantiles;
+pub mod Metrics;
+pub mod Replications;
+pub mod BatchMeans;
+pub mod Warmup;
+pub mod Sequential;
@@ -2,7 +2,11 @@ use std::{
     borrow::BorrowMut, cell::RefCell, collections::{HashMap, LinkedList, VecDeque}, hash::Hash
 };
 
-use crate::{Events::Event, Stations::Station::{IEventManager, Station}};
+use crate::{
+    Data::Metrics::{StationMetrics, SystemSummary},
+    Events::Event,
+    Stations::{JobClass::JobClass, Station::{IEventManager, Station}},
+};
 
 
 
@@ -11,6 +15,8 @@ use crate::{Events::Event, Stations::Station::{IEventManager, Station}};
 pub struct Engine {
     queue: VecDeque<Event>,
     stations: Vec<Box<Station>>,
+    classes: Vec<JobClass>,
+    clock: f64,
 }
 
 impl Engine {
@@ -18,6 +24,8 @@ impl Engine {
         static mut INSTANCE: Engine = Engine {
             queue: VecDeque::new(),
             stations: Vec::new(),
+            classes: Vec::new(),
+            clock: 0.0,
         };
         unsafe {
             return &mut INSTANCE;
@@ -25,20 +33,32 @@ impl Engine {
     }
 
     pub fn new()-> Self{
-        Engine { queue: VecDeque::new(), stations: Vec::new() }
+        Engine { queue: VecDeque::new(), stations: Vec::new(), classes: Vec::new(), clock: 0.0 }
     }
 
     pub fn enqueue(&mut self, event: Event) {
         let mut iter = (&mut self.queue).into_iter();
         if let Some(i) =  iter.position(|evt| evt.occurTime > event.occurTime){
-            self.queue.insert(i, event.clone());
+            self.queue.insert(i, event);
+        } else {
+            self.queue.push_back(event);
+        }
+    }
+
+    /// Removes a pending event, returns false if it was not scheduled.
+    pub fn cancel(&mut self, event: &Event) -> bool {
+        if let Some(i) = self.queue.iter().position(|evt| evt == event) {
+            self.queue.remove(i);
+            true
+        } else {
+            false
         }
-        self.queue.push_back(event);
     }
 
     pub fn tick(&mut self ) {
         if !self.queue.is_empty() {
             let evt = self.queue.pop_front().unwrap();
+            self.clock = self.clock.max(evt.occurTime);
             let dest = &evt.destination;
 
             for station in &mut self.stations {
@@ -55,6 +75,20 @@ impl Engine {
         &self.stations
     }
 
+    pub fn station(&self, name: &str) -> Option<&Station> {
+        self.stations.iter().find(|s| s.name() == name).map(|s| s.as_ref())
+    }
+
+    /// Derived metrics of every station, use `advance` first to bring them to a common time.
+    pub fn summary(&self) -> SystemSummary {
+        SystemSummary::new(
+            self.stations
+                .iter()
+                .map(|s| StationMetrics::new(s.name(), s.get_data()))
+                .collect(),
+        )
+    }
+
     pub fn register_station(&mut self, station: Box<Station> ) {
         self.stations.push(station);
     }
@@ -62,6 +96,55 @@ impl Engine {
     pub fn has_events(&self)->bool{
         !self.queue.is_empty()
     }
+
+    /// Time of the last event processed, or the horizon of the last run if later.
+    pub fn clock(&self) -> f64 {
+        self.clock
+    }
+
+    /// Occurrence time of the next pending event.
+    pub fn next_time(&self) -> Option<f64> {
+        self.queue.front().map(|evt| evt.occurTime)
+    }
+
+    /// Processes every event occurring before `horizon`.
+    pub fn run_until(&mut self, horizon: f64) {
+        while self.next_time().is_some_and(|t| t < horizon) {
+            self.tick();
+        }
+        self.clock = self.clock.max(horizon);
+    }
+
+    /// Brings the statistics of every station up to `time`, use after `run_until(time)`.
+    pub fn advance(&mut self, time: f64) {
+        for station in &mut self.stations {
+            station.advance(time);
+        }
+    }
+
+    /// Deletes the warm-up period: the statistics of every station restart at `time`,
+    /// use after `run_until(time)`.
+    pub fn reset_statistics(&mut self, time: f64) {
+        for station in &mut self.stations {
+            station.advance(time);
+            station.reset_statistics();
+        }
+    }
+
+    /// Registers a job class and returns its index, the value carried by `Event::class`.
+    /// The population of a closed class is placed at its reference station at time zero.
+    pub fn register_class(&mut self, class: JobClass) -> usize {
+        let index = self.classes.len();
+        for event in class.initial_jobs(index, 0.0) {
+            self.enqueue(event);
+        }
+        self.classes.push(class);
+        index
+    }
+
+    pub fn classes(&self) -> &Vec<JobClass> {
+        &self.classes
+    }
 }
 
 #[cfg(test)]
@@ -84,6 +167,18 @@ mod tests {
         Engine::instance().enqueue(event);
     }
 
+    #[test]
+    fn test_enqueue_once() {
+        let mut engine = Engine::new();
+        let at = |time: f64| Event::new(DefaultType::ARRIVAL.into(), 0.0, time, 0.0, time, "none".to_string());
+        engine.enqueue(at(2.0));
+        engine.enqueue(at(1.0));
+        engine.enqueue(at(3.0));
+        // an event placed before a later one is not pushed at the back as well
+        let times: Vec<f64> = engine.queue.iter().map(|e| e.occurTime).collect();
+        assert_eq!(times, [1.0, 2.0, 3.0]);
+    }
+
     #[test]
     fn test_ptr_cast(){
         let event = &mut Event::new(
@@ -74,6 +74,9 @@ pub struct Event {
     pub arrivalTime: f64,
     pub subType: char,
     pub destination: String,
+    pub class: usize,
+    /// Likelihood ratio accumulated by the job under importance sampling, 1 otherwise.
+    pub likelihood: f64,
 }
 
 
@@ -95,6 +98,8 @@ impl Event {
             arrivalTime,
             subType: 'N',
             destination,
+            class: 0,
+            likelihood: 1.0,
         }
     }
 
@@ -107,6 +112,8 @@ impl Event {
             arrivalTime: clock + Exponential(1.0),
             subType: DefaultType::ARRIVAL.to_char(),
             destination: "None".to_string(),
+            class: 0,
+            likelihood: 1.0,
         }
     }
     pub fn gen_departure(clock: f64) -> Self{
@@ -117,7 +124,9 @@ impl Event {
             serviceTime: 0.0,
             arrivalTime: clock - Exponential(3.0),
             subType: DefaultType::NOEVENT.to_char(),
-            destination: "None".to_string()
+            destination: "None".to_string(),
+            class: 0,
+            likelihood: 1.0,
         }
     }
 }
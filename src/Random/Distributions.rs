@@ -0,0 +1,1107 @@
+/* --------------------------------------------------------------------------
+ * Distribution objects pairing the variate generators of rvgs with the
+ * pdf, cdf and idf of rvms for the same random variable.
+ *
+ * Discrete distributions take and return their values as f64: pdf and cdf
+ * are zero below the support, pdf is zero on non integer values and cdf is
+ * evaluated at the integer part of x.
+ * --------------------------------------------------------------------------
+ */
+
+use super::{
+    rngs::{with_global_source, UniformSource},
+    rvgs::Variates,
+    rvms::*,
+};
+
+pub trait Distribution {
+    fn sample(&self, rng: &mut dyn Variates) -> f64;
+    fn pdf(&self, x: f64) -> f64;
+    fn cdf(&self, x: f64) -> f64;
+    fn idf(&self, u: f64) -> f64;
+    fn mean(&self) -> f64;
+    fn variance(&self) -> f64;
+
+    /// Inverse-transform sample: exactly one uniform per variate, monotone in it.
+    fn sample_inverse(&self, rng: &mut dyn Variates) -> f64 {
+        self.idf(rng.Random())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SamplingMode {
+    /// The rvgs generator of the distribution.
+    #[default]
+    Native,
+    /// Inversion through the rvms idf, needed by common random numbers and antithetic variates.
+    Inversion,
+}
+
+/// A distribution together with the way its variates are generated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Variable<D: Distribution> {
+    pub distribution: D,
+    pub mode: SamplingMode,
+}
+
+impl<D: Distribution> Variable<D> {
+    pub fn new(distribution: D, mode: SamplingMode) -> Self {
+        Variable { distribution, mode }
+    }
+
+    pub fn inversion(distribution: D) -> Self {
+        Variable::new(distribution, SamplingMode::Inversion)
+    }
+}
+
+impl<D: Distribution> Distribution for Variable<D> {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        match self.mode {
+            SamplingMode::Native => self.distribution.sample(rng),
+            SamplingMode::Inversion => self.distribution.sample_inverse(rng),
+        }
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        self.distribution.pdf(x)
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        self.distribution.cdf(x)
+    }
+    fn idf(&self, u: f64) -> f64 {
+        self.distribution.idf(u)
+    }
+    fn mean(&self) -> f64 {
+        self.distribution.mean()
+    }
+    fn variance(&self) -> f64 {
+        self.distribution.variance()
+    }
+}
+
+/// Closure sampling `distribution` from `stream` of the global source,
+/// usable wherever a service-time function is expected.
+pub fn sampler(distribution: impl Distribution + 'static, stream: usize) -> impl FnMut() -> f64 {
+    move || with_global_source(|global| distribution.sample(&mut global.stream(stream)))
+}
+
+/// Closure sampling `distribution` from a source of its own.
+pub fn sampler_with(
+    distribution: impl Distribution + 'static,
+    mut source: impl UniformSource + 'static,
+) -> impl FnMut() -> f64 {
+    move || distribution.sample(&mut source)
+}
+
+fn discrete_pdf(x: f64, pdf: impl Fn(u32) -> f64) -> f64 {
+    if x < 0.0 || x.fract() != 0.0 {
+        0.0
+    } else {
+        pdf(x as u32)
+    }
+}
+
+fn discrete_cdf(x: f64, cdf: impl Fn(u32) -> f64) -> f64 {
+    if x < 0.0 {
+        0.0
+    } else {
+        cdf(x.floor() as u32)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bernoulli {
+    pub p: f64,
+}
+
+impl Bernoulli {
+    pub fn new(p: f64) -> Self {
+        debug_assert!(p > 0.0 && p < 1.0, "Use 0.0 < p < 1.0");
+        Bernoulli { p }
+    }
+}
+
+impl Distribution for Bernoulli {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Bernoulli(self.p) as f64
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x == 0.0 || x == 1.0 {
+            pdfBernoulli(self.p, x as u32)
+        } else {
+            0.0
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        discrete_cdf(x, |x| cdfBernoulli(self.p, x.min(1)))
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfBernoulli(self.p, u) as f64
+    }
+    fn mean(&self) -> f64 {
+        self.p
+    }
+    fn variance(&self) -> f64 {
+        self.p * (1.0 - self.p)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Binomial {
+    pub n: u32,
+    pub p: f64,
+}
+
+impl Binomial {
+    pub fn new(n: u32, p: f64) -> Self {
+        debug_assert!(n > 0 && p > 0.0 && p < 1.0, "Use n > 0 and 0.0 < p < 1.0");
+        Binomial { n, p }
+    }
+}
+
+impl Distribution for Binomial {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Binomial(self.n, self.p) as f64
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x > self.n as f64 {
+            0.0
+        } else {
+            discrete_pdf(x, |x| pdfBinomial(self.n, self.p, x))
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        discrete_cdf(x, |x| cdfBinomial(self.n, self.p, x.min(self.n)))
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfBinomial(self.n, self.p, u) as f64
+    }
+    fn mean(&self) -> f64 {
+        self.n as f64 * self.p
+    }
+    fn variance(&self) -> f64 {
+        self.n as f64 * self.p * (1.0 - self.p)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Equilikely {
+    pub a: u32,
+    pub b: u32,
+}
+
+impl Equilikely {
+    pub fn new(a: u32, b: u32) -> Self {
+        debug_assert!(a <= b, "Use a <= b");
+        Equilikely { a, b }
+    }
+}
+
+impl Distribution for Equilikely {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Equilikely(self.a as i64, self.b as i64) as f64
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x < self.a as f64 || x > self.b as f64 {
+            0.0
+        } else {
+            discrete_pdf(x, |x| pdfEquilikely(self.a, self.b, x))
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        if x < self.a as f64 {
+            0.0
+        } else {
+            discrete_cdf(x, |x| cdfEquilikely(self.a, self.b, x.min(self.b)))
+        }
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfEquilikely(self.a, self.b, u) as f64
+    }
+    fn mean(&self) -> f64 {
+        (self.a + self.b) as f64 / 2.0
+    }
+    fn variance(&self) -> f64 {
+        let width = (self.b - self.a + 1) as f64;
+        (width * width - 1.0) / 12.0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Geometric {
+    pub p: f64,
+}
+
+impl Geometric {
+    pub fn new(p: f64) -> Self {
+        debug_assert!(p > 0.0 && p < 1.0, "Use 0.0 < p < 1.0");
+        Geometric { p }
+    }
+}
+
+impl Distribution for Geometric {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Geometric(self.p) as f64
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        discrete_pdf(x, |x| pdfGeometric(self.p, x))
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        discrete_cdf(x, |x| cdfGeometric(self.p, x))
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfGeometric(self.p, u) as f64
+    }
+    fn mean(&self) -> f64 {
+        self.p / (1.0 - self.p)
+    }
+    fn variance(&self) -> f64 {
+        self.p / ((1.0 - self.p) * (1.0 - self.p))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pascal {
+    pub n: u32,
+    pub p: f64,
+}
+
+impl Pascal {
+    pub fn new(n: u32, p: f64) -> Self {
+        debug_assert!(n > 0 && p > 0.0 && p < 1.0, "Use n > 0 and 0.0 < p < 1.0");
+        Pascal { n, p }
+    }
+}
+
+impl Distribution for Pascal {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Pascal(self.n, self.p) as f64
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        discrete_pdf(x, |x| pdfPascal(self.n, self.p, x))
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        discrete_cdf(x, |x| cdfPascal(self.n, self.p, x))
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfPascal(self.n, self.p, u) as f64
+    }
+    fn mean(&self) -> f64 {
+        self.n as f64 * self.p / (1.0 - self.p)
+    }
+    fn variance(&self) -> f64 {
+        self.n as f64 * self.p / ((1.0 - self.p) * (1.0 - self.p))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Poisson {
+    pub m: f64,
+}
+
+impl Poisson {
+    pub fn new(m: f64) -> Self {
+        debug_assert!(m > 0.0, "Use m > 0.0");
+        Poisson { m }
+    }
+}
+
+impl Distribution for Poisson {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Poisson(self.m) as f64
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        discrete_pdf(x, |x| pdfPoisson(self.m, x))
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        discrete_cdf(x, |x| cdfPoisson(self.m, x))
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfPoisson(self.m, u) as f64
+    }
+    fn mean(&self) -> f64 {
+        self.m
+    }
+    fn variance(&self) -> f64 {
+        self.m
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Uniform {
+    pub a: f64,
+    pub b: f64,
+}
+
+impl Uniform {
+    pub fn new(a: f64, b: f64) -> Self {
+        debug_assert!(a < b, "Use a < b");
+        Uniform { a, b }
+    }
+}
+
+impl Distribution for Uniform {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Uniform(self.a, self.b)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x < self.a || x > self.b {
+            0.0
+        } else {
+            pdfUniform(self.a, self.b, x)
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        cdfUniform(self.a, self.b, x.clamp(self.a, self.b))
+    }
+    fn idf(&self, u: f64) -> f64 {
+        dfUniform(self.a, self.b, u)
+    }
+    fn mean(&self) -> f64 {
+        (self.a + self.b) / 2.0
+    }
+    fn variance(&self) -> f64 {
+        (self.b - self.a) * (self.b - self.a) / 12.0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Exponential {
+    pub m: f64,
+}
+
+impl Exponential {
+    pub fn new(m: f64) -> Self {
+        debug_assert!(m > 0.0, "Use m > 0.0");
+        Exponential { m }
+    }
+}
+
+impl Distribution for Exponential {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Exponential(self.m)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x < 0.0 {
+            0.0
+        } else {
+            pdfExponential(self.m, x)
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            0.0
+        } else {
+            cdfExponential(self.m, x)
+        }
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfExponential(self.m, u)
+    }
+    fn mean(&self) -> f64 {
+        self.m
+    }
+    fn variance(&self) -> f64 {
+        self.m * self.m
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Erlang {
+    pub n: u32,
+    pub b: f64,
+}
+
+impl Erlang {
+    pub fn new(n: u32, b: f64) -> Self {
+        debug_assert!(n > 0 && b > 0.0, "Use n > 0 and b > 0.0");
+        Erlang { n, b }
+    }
+}
+
+impl Distribution for Erlang {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Erlang(self.n, self.b)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            0.0
+        } else {
+            pdfErlang(self.n, self.b, x)
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            0.0
+        } else {
+            cdfErlang(self.n, self.b, x)
+        }
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfErlang(self.n, self.b, u)
+    }
+    fn mean(&self) -> f64 {
+        self.n as f64 * self.b
+    }
+    fn variance(&self) -> f64 {
+        self.n as f64 * self.b * self.b
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Normal {
+    pub m: f64,
+    pub s: f64,
+}
+
+impl Normal {
+    pub fn new(m: f64, s: f64) -> Self {
+        debug_assert!(s > 0.0, "Use s > 0.0");
+        Normal { m, s }
+    }
+}
+
+impl Distribution for Normal {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Normal(self.m, self.s)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        pdfNormal(self.m, self.s, x)
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        cdfNormal(self.m, self.s, x)
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfNormal(self.m, self.s, u)
+    }
+    fn mean(&self) -> f64 {
+        self.m
+    }
+    fn variance(&self) -> f64 {
+        self.s * self.s
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lognormal {
+    pub a: f64,
+    pub b: f64,
+}
+
+impl Lognormal {
+    pub fn new(a: f64, b: f64) -> Self {
+        debug_assert!(b > 0.0, "Use b > 0.0");
+        Lognormal { a, b }
+    }
+}
+
+impl Distribution for Lognormal {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Lognormal(self.a, self.b)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            0.0
+        } else {
+            pdfLognormal(self.a, self.b, x)
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            0.0
+        } else {
+            cdfLognormal(self.a, self.b, x)
+        }
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfLognormal(self.a, self.b, u)
+    }
+    fn mean(&self) -> f64 {
+        (self.a + 0.5 * self.b * self.b).exp()
+    }
+    fn variance(&self) -> f64 {
+        ((self.b * self.b).exp() - 1.0) * (2.0 * self.a + self.b * self.b).exp()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Chisquare {
+    pub n: u32,
+}
+
+impl Chisquare {
+    pub fn new(n: u32) -> Self {
+        debug_assert!(n > 0, "Use n > 0");
+        Chisquare { n }
+    }
+}
+
+impl Distribution for Chisquare {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Chisquare(self.n)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            0.0
+        } else {
+            pdfChisquare(self.n, x)
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            0.0
+        } else {
+            cdfChisquare(self.n, x)
+        }
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfChisquare(self.n, u)
+    }
+    fn mean(&self) -> f64 {
+        self.n as f64
+    }
+    fn variance(&self) -> f64 {
+        2.0 * self.n as f64
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Student {
+    pub n: u32,
+}
+
+impl Student {
+    pub fn new(n: u32) -> Self {
+        debug_assert!(n > 0, "Use n > 0");
+        Student { n }
+    }
+}
+
+impl Distribution for Student {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Student(self.n)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        pdfStudent(self.n, x)
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        cdfStudent(self.n as f64, x)
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfStudent(self.n, u)
+    }
+    /// Undefined (NaN) for n = 1.
+    fn mean(&self) -> f64 {
+        if self.n > 1 {
+            0.0
+        } else {
+            f64::NAN
+        }
+    }
+    /// Infinite for n <= 2.
+    fn variance(&self) -> f64 {
+        if self.n > 2 {
+            self.n as f64 / (self.n as f64 - 2.0)
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+fn gamma(a: f64) -> f64 {
+    LogGamma(a).exp()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Weibull {
+    pub a: f64,
+    pub b: f64,
+}
+
+impl Weibull {
+    /// Shape `a`, scale `b`.
+    pub fn new(a: f64, b: f64) -> Self {
+        debug_assert!(a > 0.0 && b > 0.0, "Use a > 0.0 and b > 0.0");
+        Weibull { a, b }
+    }
+}
+
+impl Distribution for Weibull {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Weibull(self.a, self.b)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x < 0.0 {
+            0.0
+        } else {
+            pdfWeibull(self.a, self.b, x)
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            0.0
+        } else {
+            cdfWeibull(self.a, self.b, x)
+        }
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfWeibull(self.a, self.b, u)
+    }
+    fn mean(&self) -> f64 {
+        self.b * gamma(1.0 + 1.0 / self.a)
+    }
+    fn variance(&self) -> f64 {
+        let m = gamma(1.0 + 1.0 / self.a);
+        self.b * self.b * (gamma(1.0 + 2.0 / self.a) - m * m)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pareto {
+    pub a: f64,
+    pub b: f64,
+}
+
+impl Pareto {
+    /// Shape `a`, minimum value `b`.
+    pub fn new(a: f64, b: f64) -> Self {
+        debug_assert!(a > 0.0 && b > 0.0, "Use a > 0.0 and b > 0.0");
+        Pareto { a, b }
+    }
+}
+
+impl Distribution for Pareto {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Pareto(self.a, self.b)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x < self.b {
+            0.0
+        } else {
+            pdfPareto(self.a, self.b, x)
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        if x <= self.b {
+            0.0
+        } else {
+            cdfPareto(self.a, self.b, x)
+        }
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfPareto(self.a, self.b, u)
+    }
+    /// Infinite for a <= 1.
+    fn mean(&self) -> f64 {
+        if self.a > 1.0 {
+            self.a * self.b / (self.a - 1.0)
+        } else {
+            f64::INFINITY
+        }
+    }
+    /// Infinite for a <= 2.
+    fn variance(&self) -> f64 {
+        if self.a > 2.0 {
+            self.b * self.b * self.a / ((self.a - 1.0) * (self.a - 1.0) * (self.a - 2.0))
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundedPareto {
+    pub a: f64,
+    pub l: f64,
+    pub h: f64,
+}
+
+impl BoundedPareto {
+    /// Shape `a` truncated to `[l, h]`.
+    pub fn new(a: f64, l: f64, h: f64) -> Self {
+        debug_assert!(a > 0.0 && l > 0.0 && l < h, "Use a > 0.0 and 0.0 < l < h");
+        BoundedPareto { a, l, h }
+    }
+
+    /// E[X^k] = C (h^(k-a) - l^(k-a)) / (k - a), C = a l^a / (1 - (l/h)^a).
+    fn moment(&self, k: f64) -> f64 {
+        let c = self.a * self.l.powf(self.a) / (1.0 - (self.l / self.h).powf(self.a));
+        if (k - self.a).abs() < TINY {
+            c * (self.h / self.l).ln()
+        } else {
+            c * (self.h.powf(k - self.a) - self.l.powf(k - self.a)) / (k - self.a)
+        }
+    }
+}
+
+impl Distribution for BoundedPareto {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.BoundedPareto(self.a, self.l, self.h)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x < self.l || x > self.h {
+            0.0
+        } else {
+            pdfBoundedPareto(self.a, self.l, self.h, x)
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        cdfBoundedPareto(self.a, self.l, self.h, x.clamp(self.l, self.h))
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfBoundedPareto(self.a, self.l, self.h, u)
+    }
+    fn mean(&self) -> f64 {
+        self.moment(1.0)
+    }
+    fn variance(&self) -> f64 {
+        let m = self.moment(1.0);
+        self.moment(2.0) - m * m
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gamma {
+    pub a: f64,
+    pub b: f64,
+}
+
+impl Gamma {
+    /// Shape `a` (any positive value), scale `b`.
+    pub fn new(a: f64, b: f64) -> Self {
+        debug_assert!(a > 0.0 && b > 0.0, "Use a > 0.0 and b > 0.0");
+        Gamma { a, b }
+    }
+}
+
+impl Distribution for Gamma {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Gamma(self.a, self.b)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            0.0
+        } else {
+            pdfGamma(self.a, self.b, x)
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            0.0
+        } else {
+            cdfGamma(self.a, self.b, x)
+        }
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfGamma(self.a, self.b, u)
+    }
+    fn mean(&self) -> f64 {
+        self.a * self.b
+    }
+    fn variance(&self) -> f64 {
+        self.a * self.b * self.b
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Beta {
+    pub a: f64,
+    pub b: f64,
+}
+
+impl Beta {
+    pub fn new(a: f64, b: f64) -> Self {
+        debug_assert!(a > 0.0 && b > 0.0, "Use a > 0.0 and b > 0.0");
+        Beta { a, b }
+    }
+}
+
+impl Distribution for Beta {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Beta(self.a, self.b)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x <= 0.0 || x >= 1.0 {
+            0.0
+        } else {
+            pdfBeta(self.a, self.b, x)
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        cdfBeta(self.a, self.b, x.clamp(0.0, 1.0))
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfBeta(self.a, self.b, u)
+    }
+    fn mean(&self) -> f64 {
+        self.a / (self.a + self.b)
+    }
+    fn variance(&self) -> f64 {
+        let s = self.a + self.b;
+        self.a * self.b / (s * s * (s + 1.0))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Triangular {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+}
+
+impl Triangular {
+    /// Support `[a, b]` with mode `c`.
+    pub fn new(a: f64, b: f64, c: f64) -> Self {
+        debug_assert!(a < b && a <= c && c <= b, "Use a <= c <= b and a < b");
+        Triangular { a, b, c }
+    }
+}
+
+impl Distribution for Triangular {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Triangular(self.a, self.b, self.c)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x < self.a || x > self.b {
+            0.0
+        } else {
+            pdfTriangular(self.a, self.b, self.c, x)
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        cdfTriangular(self.a, self.b, self.c, x.clamp(self.a, self.b))
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfTriangular(self.a, self.b, self.c, u)
+    }
+    fn mean(&self) -> f64 {
+        (self.a + self.b + self.c) / 3.0
+    }
+    fn variance(&self) -> f64 {
+        let (a, b, c) = (self.a, self.b, self.c);
+        (a * a + b * b + c * c - a * b - a * c - b * c) / 18.0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Loglogistic {
+    pub a: f64,
+    pub b: f64,
+}
+
+impl Loglogistic {
+    /// Scale `a`, shape `b`.
+    pub fn new(a: f64, b: f64) -> Self {
+        debug_assert!(a > 0.0 && b > 0.0, "Use a > 0.0 and b > 0.0");
+        Loglogistic { a, b }
+    }
+}
+
+impl Distribution for Loglogistic {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Loglogistic(self.a, self.b)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            0.0
+        } else {
+            pdfLoglogistic(self.a, self.b, x)
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        if x <= 0.0 {
+            0.0
+        } else {
+            cdfLoglogistic(self.a, self.b, x)
+        }
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfLoglogistic(self.a, self.b, u)
+    }
+    /// Infinite for b <= 1.
+    fn mean(&self) -> f64 {
+        if self.b > 1.0 {
+            let t = std::f64::consts::PI / self.b;
+            self.a * t / t.sin()
+        } else {
+            f64::INFINITY
+        }
+    }
+    /// Infinite for b <= 2.
+    fn variance(&self) -> f64 {
+        if self.b > 2.0 {
+            let t = std::f64::consts::PI / self.b;
+            self.a * self.a * (2.0 * t / (2.0 * t).sin() - t * t / (t.sin() * t.sin()))
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cauchy {
+    pub a: f64,
+    pub b: f64,
+}
+
+impl Cauchy {
+    /// Location `a`, scale `b`.
+    pub fn new(a: f64, b: f64) -> Self {
+        debug_assert!(b > 0.0, "Use b > 0.0");
+        Cauchy { a, b }
+    }
+}
+
+impl Distribution for Cauchy {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        rng.Cauchy(self.a, self.b)
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        pdfCauchy(self.a, self.b, x)
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        cdfCauchy(self.a, self.b, x)
+    }
+    fn idf(&self, u: f64) -> f64 {
+        idfCauchy(self.a, self.b, u)
+    }
+    /// Undefined.
+    fn mean(&self) -> f64 {
+        f64::NAN
+    }
+    /// Undefined.
+    fn variance(&self) -> f64 {
+        f64::NAN
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Random::rngs::RandomGenerator;
+
+    use super::*;
+
+    fn check_moments(distribution: &dyn Distribution, generator: &mut RandomGenerator) {
+        let n = 20000;
+        let samples: Vec<f64> = (0..n).map(|_| distribution.sample(generator)).collect();
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let variance = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1) as f64;
+        let tolerance = 5.0 * (distribution.variance() / n as f64).sqrt();
+        assert!((mean - distribution.mean()).abs() < tolerance, "mean {} expected {}", mean, distribution.mean());
+        assert!(
+            (variance - distribution.variance()).abs() < 0.1 * distribution.variance(),
+            "variance {} expected {}",
+            variance,
+            distribution.variance()
+        );
+    }
+
+    fn check_inverse(distribution: &dyn Distribution) {
+        for u in [0.05, 0.3, 0.5, 0.77, 0.95] {
+            let x = distribution.idf(u);
+            assert!(distribution.cdf(x) >= u - 1e-6, "cdf(idf({})) = {}", u, distribution.cdf(x));
+            assert!(distribution.pdf(x) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_distributions() {
+        let mut generator = RandomGenerator::new();
+        generator.PutSeed(987654321);
+        let distributions: Vec<Box<dyn Distribution>> = vec![
+            Box::new(Bernoulli::new(0.3)),
+            Box::new(Binomial::new(12, 0.4)),
+            Box::new(Equilikely::new(2, 9)),
+            Box::new(Geometric::new(0.6)),
+            Box::new(Pascal::new(3, 0.4)),
+            Box::new(Poisson::new(4.5)),
+            Box::new(Uniform::new(-1.0, 3.0)),
+            Box::new(Exponential::new(2.0)),
+            Box::new(Erlang::new(3, 1.5)),
+            Box::new(Normal::new(5.0, 2.0)),
+            Box::new(Lognormal::new(0.5, 0.4)),
+            Box::new(Chisquare::new(6)),
+            Box::new(Student::new(8)),
+        ];
+        for distribution in &distributions {
+            check_moments(distribution.as_ref(), &mut generator);
+            check_inverse(distribution.as_ref());
+        }
+    }
+
+    #[test]
+    fn test_continuous_cdf_matches_idf() {
+        let distributions: Vec<Box<dyn Distribution>> = vec![
+            Box::new(Exponential::new(2.0)),
+            Box::new(Erlang::new(4, 0.5)),
+            Box::new(Normal::new(-1.0, 3.0)),
+            Box::new(Lognormal::new(1.0, 0.5)),
+            Box::new(Chisquare::new(5)),
+            Box::new(Student::new(10)),
+        ];
+        for distribution in &distributions {
+            for u in [0.01, 0.25, 0.5, 0.9, 0.99] {
+                assert!((distribution.cdf(distribution.idf(u)) - u).abs() < 1e-6);
+            }
+        }
+        assert!((Student::new(10).idf(0.975) - 2.228139).abs() < 1e-5);
+        assert!((Student::new(10).pdf(1.0) - 0.230362).abs() < 1e-5);
+        assert_eq!(Binomial::new(6, 0.5).idf(0.5), 3.0);
+        assert!((Binomial::new(6, 0.5).cdf(0.0) - 0.015625).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_inversion_mode() {
+        let mut a = RandomGenerator::new();
+        a.PutSeed(5555);
+        let mut b = RandomGenerator::new();
+        b.PutSeed(5555);
+        let slow = Variable::inversion(Erlang::new(3, 2.0));
+        let fast = Variable::inversion(Erlang::new(3, 1.0));
+        for _ in 0..200 {
+            let x = slow.sample(&mut a);
+            let y = fast.sample(&mut b);
+            assert!(x > y);
+            assert_eq!(a.GetSeed(), b.GetSeed());
+        }
+        b.Random();
+        slow.sample(&mut a);
+        assert_eq!(a.GetSeed(), b.GetSeed(), "one uniform per variate");
+        check_moments(&Variable::inversion(Poisson::new(3.0)), &mut a);
+        check_moments(&Variable::new(Normal::new(1.0, 0.5), SamplingMode::Native), &mut a);
+    }
+
+    #[test]
+    fn test_heavy_tailed_distributions() {
+        let mut generator = RandomGenerator::new();
+        generator.PutSeed(13579);
+        let distributions: Vec<Box<dyn Distribution>> = vec![
+            Box::new(Weibull::new(1.5, 2.0)),
+            Box::new(Pareto::new(6.0, 1.0)),
+            Box::new(BoundedPareto::new(1.2, 1.0, 50.0)),
+            Box::new(BoundedPareto::new(2.0, 1.0, 10.0)),
+            Box::new(Gamma::new(0.6, 2.0)),
+            Box::new(Gamma::new(3.7, 0.5)),
+            Box::new(Beta::new(2.0, 5.0)),
+            Box::new(Beta::new(0.7, 0.9)),
+            Box::new(Triangular::new(1.0, 4.0, 2.0)),
+            Box::new(Loglogistic::new(2.0, 10.0)),
+        ];
+        for distribution in &distributions {
+            check_moments(distribution.as_ref(), &mut generator);
+            for u in [0.01, 0.2, 0.5, 0.8, 0.99] {
+                let x = distribution.idf(u);
+                assert!((distribution.cdf(x) - u).abs() < 1e-6, "cdf(idf({})) = {}", u, distribution.cdf(x));
+            }
+        }
+        let cauchy = Cauchy::new(1.0, 2.0);
+        assert!((cauchy.idf(0.75) - 3.0).abs() < 1e-9);
+        let median = {
+            let mut samples: Vec<f64> = (0..10001).map(|_| cauchy.sample(&mut generator)).collect();
+            samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            samples[5000]
+        };
+        assert!((median - 1.0).abs() < 0.1, "median {}", median);
+        assert!((Gamma::new(2.5, 1.0).cdf(2.0) - 0.450584).abs() < 1e-5);
+        assert!((Weibull::new(1.0, 3.0).cdf(3.0) - Exponential::new(3.0).cdf(3.0)).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,156 @@
+/* --------------------------------------------------------------------------
+ * Empirical distribution of a sample x(0) <= x(1) <= ... <= x(n-1).
+ *
+ * Step (default): each observation has mass 1/n, idf(u) = x(ceil(n u) - 1).
+ *
+ * Interpolated: the cdf is piecewise linear through the order statistics,
+ * F(x(i)) = i / (n - 1), i.e. uniform with mass 1/(n - 1) on every
+ * segment [x(i), x(i+1)].
+ * --------------------------------------------------------------------------
+ */
+
+use super::{rvgs::Variates, Distributions::Distribution};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Empirical {
+    values: Vec<f64>,
+    interpolate: bool,
+}
+
+impl Empirical {
+    pub fn new(mut sample: Vec<f64>) -> Self {
+        debug_assert!(!sample.is_empty(), "Sample should not be empty");
+        debug_assert!(sample.iter().all(|x| x.is_finite()), "Sample should be finite");
+        sample.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        Empirical {
+            values: sample,
+            interpolate: false,
+        }
+    }
+
+    /// Linear interpolation between consecutive order statistics, needs two observations.
+    pub fn interpolated(sample: Vec<f64>) -> Self {
+        debug_assert!(sample.len() > 1, "Interpolation needs at least two observations");
+        Empirical {
+            interpolate: true,
+            ..Empirical::new(sample)
+        }
+    }
+
+    /// Order statistics of the sample.
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    fn segments(&self) -> f64 {
+        (self.values.len() - 1) as f64
+    }
+}
+
+impl Distribution for Empirical {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        self.idf(rng.Random())
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        let x0 = self.values[0];
+        let xn = self.values[self.len() - 1];
+        if self.interpolate {
+            if x < x0 || x > xn || x0 == xn {
+                return 0.0;
+            }
+            let i = self.values.partition_point(|v| *v <= x).clamp(1, self.len() - 1) - 1;
+            let width = self.values[i + 1] - self.values[i];
+            if width > 0.0 {
+                1.0 / (self.segments() * width)
+            } else {
+                0.0
+            }
+        } else {
+            self.values.iter().filter(|v| **v == x).count() as f64 / self.len() as f64
+        }
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        let count = self.values.partition_point(|v| *v <= x);
+        if !self.interpolate {
+            return count as f64 / self.len() as f64;
+        }
+        if count == 0 {
+            0.0
+        } else if count == self.len() {
+            1.0
+        } else {
+            let i = count - 1;
+            let (a, b) = (self.values[i], self.values[i + 1]);
+            (i as f64 + (x - a) / (b - a)) / self.segments()
+        }
+    }
+    fn idf(&self, u: f64) -> f64 {
+        if self.interpolate {
+            let p = u * self.segments();
+            let i = (p.floor() as usize).min(self.len() - 2);
+            let (a, b) = (self.values[i], self.values[i + 1]);
+            a + (p - i as f64) * (b - a)
+        } else {
+            let i = (u * self.len() as f64).ceil() as usize;
+            self.values[i.clamp(1, self.len()) - 1]
+        }
+    }
+    fn mean(&self) -> f64 {
+        if self.interpolate {
+            self.values.windows(2).map(|w| 0.5 * (w[0] + w[1])).sum::<f64>() / self.segments()
+        } else {
+            self.values.iter().sum::<f64>() / self.len() as f64
+        }
+    }
+    fn variance(&self) -> f64 {
+        let mean = self.mean();
+        let second = if self.interpolate {
+            self.values
+                .windows(2)
+                .map(|w| (w[0] * w[0] + w[0] * w[1] + w[1] * w[1]) / 3.0)
+                .sum::<f64>()
+                / self.segments()
+        } else {
+            self.values.iter().map(|x| x * x).sum::<f64>() / self.len() as f64
+        };
+        second - mean * mean
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_and_interpolated() {
+        let step = Empirical::new(vec![3.0, 1.0, 2.0, 2.0]);
+        assert_eq!(step.values(), &[1.0, 2.0, 2.0, 3.0]);
+        assert_eq!(step.cdf(2.0), 0.75);
+        assert_eq!(step.pdf(2.0), 0.5);
+        assert_eq!(step.idf(0.3), 2.0);
+        assert_eq!(step.idf(0.0), 1.0);
+        assert_eq!(step.idf(1.0), 3.0);
+        assert_eq!(step.mean(), 2.0);
+        assert_eq!(step.variance(), 0.5);
+
+        let linear = Empirical::interpolated(vec![0.0, 4.0, 1.0]);
+        assert_eq!(linear.cdf(0.5), 0.25);
+        assert_eq!(linear.cdf(2.5), 0.75);
+        assert_eq!(linear.pdf(0.5), 0.5);
+        assert_eq!(linear.pdf(2.5), 1.0 / 6.0);
+        for u in [0.1, 0.4, 0.5, 0.9] {
+            assert!((linear.cdf(linear.idf(u)) - u).abs() < 1e-12);
+        }
+        // equal mixture of U(0, 1) and U(1, 4)
+        assert!((linear.mean() - 1.5).abs() < 1e-12);
+        assert!((linear.variance() - (0.5 * (1.0 / 3.0 + 7.0) - 2.25)).abs() < 1e-12);
+    }
+}
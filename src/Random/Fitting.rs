@@ -0,0 +1,375 @@
+/* --------------------------------------------------------------------------
+ * Parameter estimation for the distributions of rvgs, by maximum
+ * likelihood or by the method of moments, and ranking of candidate
+ * families for a sample.
+ *
+ *  family       maximum likelihood                  moments
+ *  Uniform      min, max                            mean -/+ sqrt(3 var)
+ *  Exponential  mean                                mean
+ *  Erlang       rounded gamma shape, mean / n        n = round(mean^2 / var)
+ *  Gamma        Newton on ln a - psi(a) = ln mean - mean ln x
+ *                                                   a = mean^2 / var
+ *  Normal       mean, sqrt(biased variance)         mean, sqrt(variance)
+ *  Lognormal    moments of ln x                     b^2 = ln(1 + var / mean^2)
+ *  Weibull      Newton on the shape equation        shape from the scv
+ *  Pareto       min, n / sum ln(x / min)            a = 1 + sqrt(1 + mean^2 / var)
+ *  Beta         Newton on the digamma equations     moments
+ *  Poisson      mean                                mean
+ *  Geometric    mean / (1 + mean)                   mean / (1 + mean)
+ *
+ * The variance used by the method of moments is the unbiased one.
+ * --------------------------------------------------------------------------
+ */
+
+use super::{
+    rvms::{LogGamma, TINY},
+    Distributions::{
+        Beta, Distribution, Erlang, Exponential, Gamma, Geometric, Lognormal, Normal, Pareto, Poisson,
+        Uniform, Weibull,
+    },
+    GoodnessOfFit::{anderson_darling, chi_square, kolmogorov_smirnov},
+    StreamTests::TestOutcome,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Family {
+    Uniform,
+    Exponential,
+    Erlang,
+    Gamma,
+    Normal,
+    Lognormal,
+    Weibull,
+    Pareto,
+    Beta,
+    Poisson,
+    Geometric,
+}
+
+impl Family {
+    pub const ALL: [Family; 11] = [
+        Family::Uniform,
+        Family::Exponential,
+        Family::Erlang,
+        Family::Gamma,
+        Family::Normal,
+        Family::Lognormal,
+        Family::Weibull,
+        Family::Pareto,
+        Family::Beta,
+        Family::Poisson,
+        Family::Geometric,
+    ];
+
+    /// Number of parameters estimated from the sample.
+    pub fn parameters(&self) -> usize {
+        match self {
+            Family::Exponential | Family::Poisson | Family::Geometric => 1,
+            _ => 2,
+        }
+    }
+
+    pub fn is_discrete(&self) -> bool {
+        matches!(self, Family::Poisson | Family::Geometric)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Method {
+    #[default]
+    MaximumLikelihood,
+    Moments,
+}
+
+fn digamma(mut x: f64) -> f64 {
+    let mut result = 0.0;
+    while x < 10.0 {
+        result -= 1.0 / x;
+        x += 1.0;
+    }
+    let f = 1.0 / (x * x);
+    result + x.ln() - 0.5 / x - f * (1.0 / 12.0 - f * (1.0 / 120.0 - f * (1.0 / 252.0 - f / 240.0)))
+}
+
+fn trigamma(mut x: f64) -> f64 {
+    let mut result = 0.0;
+    while x < 10.0 {
+        result += 1.0 / (x * x);
+        x += 1.0;
+    }
+    let f = 1.0 / (x * x);
+    result + 1.0 / x + f / 2.0 + f / x * (1.0 / 6.0 - f * (1.0 / 30.0 - f / 42.0))
+}
+
+/// Gamma shape solving ln a - psi(a) = s, s > 0.
+fn gamma_shape(s: f64) -> f64 {
+    let mut a = (3.0 - s + ((s - 3.0) * (s - 3.0) + 24.0 * s).sqrt()) / (12.0 * s);
+    for _ in 0..50 {
+        let step = (a.ln() - digamma(a) - s) / (1.0 / a - trigamma(a));
+        a = (a - step).max(0.5 * a);
+        if step.abs() < TINY * a {
+            break;
+        }
+    }
+    a
+}
+
+/// Weibull shape k solving sum x^k ln x / sum x^k - 1 / k = mean ln x.
+fn weibull_shape(sample: &[f64]) -> f64 {
+    let logs: Vec<f64> = sample.iter().map(|x| x.ln()).collect();
+    let mean_log = logs.iter().sum::<f64>() / logs.len() as f64;
+    let g = |k: f64| {
+        let (mut s0, mut s1, mut s2) = (0.0, 0.0, 0.0);
+        for l in &logs {
+            let w = (k * (l - mean_log)).exp();
+            s0 += w;
+            s1 += w * l;
+            s2 += w * l * l;
+        }
+        (s1 / s0 - 1.0 / k - mean_log, s2 / s0 - (s1 / s0) * (s1 / s0) + 1.0 / (k * k))
+    };
+    let mut k = 1.2 / (logs.iter().map(|l| (l - mean_log) * (l - mean_log)).sum::<f64>() / logs.len() as f64).sqrt();
+    for _ in 0..100 {
+        let (f, df) = g(k);
+        let next = (k - f / df).max(0.5 * k);
+        if (next - k).abs() < TINY * k {
+            return next;
+        }
+        k = next;
+    }
+    k
+}
+
+fn bisect(f: impl Fn(f64) -> f64, mut low: f64, mut high: f64) -> f64 {
+    let rising = f(high) > f(low);
+    while high - low > TINY * high {
+        let mid = 0.5 * (low + high);
+        if (f(mid) > 0.0) == rising {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    0.5 * (low + high)
+}
+
+/// Estimates the parameters of `family` from `sample`, `None` if the sample is too
+/// small or falls outside the support of the family.
+pub fn fit(family: Family, sample: &[f64], method: Method) -> Option<Box<dyn Distribution>> {
+    let n = sample.len() as f64;
+    if sample.len() < 2 || sample.iter().any(|x| !x.is_finite()) {
+        return None;
+    }
+    let mean = sample.iter().sum::<f64>() / n;
+    let var = sample.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1.0);
+    let min = sample.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = sample.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let positive = min > 0.0;
+    let ml = method == Method::MaximumLikelihood;
+    if var <= 0.0 {
+        return None;
+    }
+    let fitted: Box<dyn Distribution> = match family {
+        Family::Uniform if ml => Box::new(Uniform::new(min, max)),
+        Family::Uniform => {
+            let h = (3.0 * var).sqrt();
+            Box::new(Uniform::new(mean - h, mean + h))
+        }
+        Family::Exponential if positive => Box::new(Exponential::new(mean)),
+        Family::Erlang | Family::Gamma if positive => {
+            let shape = if ml {
+                gamma_shape(mean.ln() - sample.iter().map(|x| x.ln()).sum::<f64>() / n)
+            } else {
+                mean * mean / var
+            };
+            if family == Family::Gamma {
+                Box::new(Gamma::new(shape, mean / shape))
+            } else {
+                let k = shape.round().max(1.0);
+                Box::new(Erlang::new(k as u32, mean / k))
+            }
+        }
+        Family::Normal => {
+            let s = if ml { (var * (n - 1.0) / n).sqrt() } else { var.sqrt() };
+            Box::new(Normal::new(mean, s))
+        }
+        Family::Lognormal if positive => {
+            if ml {
+                let logs: Vec<f64> = sample.iter().map(|x| x.ln()).collect();
+                let a = logs.iter().sum::<f64>() / n;
+                let b = (logs.iter().map(|l| (l - a) * (l - a)).sum::<f64>() / n).sqrt();
+                Box::new(Lognormal::new(a, b))
+            } else {
+                let b2 = (1.0 + var / (mean * mean)).ln();
+                Box::new(Lognormal::new(mean.ln() - 0.5 * b2, b2.sqrt()))
+            }
+        }
+        Family::Weibull if positive => {
+            let k = if ml {
+                weibull_shape(sample)
+            } else {
+                let scv = var / (mean * mean);
+                let ratio = |k: f64| (LogGamma(1.0 + 2.0 / k) - 2.0 * LogGamma(1.0 + 1.0 / k)).exp() - 1.0 - scv;
+                bisect(ratio, 0.05, 100.0)
+            };
+            let scale = if ml {
+                (sample.iter().map(|x| x.powf(k)).sum::<f64>() / n).powf(1.0 / k)
+            } else {
+                mean / LogGamma(1.0 + 1.0 / k).exp()
+            };
+            Box::new(Weibull::new(k, scale))
+        }
+        Family::Pareto if positive => {
+            if ml {
+                let a = n / sample.iter().map(|x| (x / min).ln()).sum::<f64>();
+                Box::new(Pareto::new(a, min))
+            } else {
+                let a = 1.0 + (1.0 + mean * mean / var).sqrt();
+                Box::new(Pareto::new(a, mean * (a - 1.0) / a))
+            }
+        }
+        Family::Beta if positive && max < 1.0 => {
+            let c = mean * (1.0 - mean) / var - 1.0;
+            if c <= 0.0 {
+                return None;
+            }
+            let (mut a, mut b) = (mean * c, (1.0 - mean) * c);
+            if ml {
+                let g1 = sample.iter().map(|x| x.ln()).sum::<f64>() / n;
+                let g2 = sample.iter().map(|x| (1.0 - x).ln()).sum::<f64>() / n;
+                for _ in 0..100 {
+                    let psi = digamma(a + b);
+                    let (f1, f2) = (digamma(a) - psi - g1, digamma(b) - psi - g2);
+                    let t = trigamma(a + b);
+                    let (j11, j22, j12) = (trigamma(a) - t, trigamma(b) - t, -t);
+                    let det = j11 * j22 - j12 * j12;
+                    let (da, db) = ((f1 * j22 - f2 * j12) / det, (f2 * j11 - f1 * j12) / det);
+                    a = (a - da).max(0.5 * a);
+                    b = (b - db).max(0.5 * b);
+                    if da.abs() < TINY * a && db.abs() < TINY * b {
+                        break;
+                    }
+                }
+            }
+            Box::new(Beta::new(a, b))
+        }
+        Family::Poisson | Family::Geometric if min >= 0.0 && sample.iter().all(|x| x.fract() == 0.0) => {
+            if family == Family::Poisson {
+                Box::new(Poisson::new(mean))
+            } else {
+                Box::new(Geometric::new(mean / (1.0 + mean)))
+            }
+        }
+        _ => return None,
+    };
+    Some(fitted)
+}
+
+/// A fitted candidate with its goodness-of-fit.
+pub struct Fit {
+    pub family: Family,
+    pub distribution: Box<dyn Distribution>,
+    pub log_likelihood: f64,
+    pub chi_square: TestOutcome,
+    pub kolmogorov_smirnov: TestOutcome,
+    /// `None` for discrete families.
+    pub anderson_darling: Option<TestOutcome>,
+}
+
+impl Fit {
+    /// Akaike information criterion, 2 k - 2 ln L.
+    pub fn aic(&self) -> f64 {
+        2.0 * self.family.parameters() as f64 - 2.0 * self.log_likelihood
+    }
+}
+
+/// Fits every family in `families` that supports the sample and ranks them by
+/// increasing Kolmogorov-Smirnov statistic, best fit first.
+pub fn rank(sample: &[f64], families: &[Family], method: Method, alpha: f64) -> Vec<Fit> {
+    let mut fits: Vec<Fit> = families
+        .iter()
+        .filter_map(|family| {
+            let distribution = fit(*family, sample, method)?;
+            let log_likelihood = sample.iter().map(|x| distribution.pdf(*x).ln()).sum();
+            let bins = ((sample.len() as f64).sqrt() as usize).clamp(5, 50);
+            Some(Fit {
+                family: *family,
+                chi_square: chi_square(sample, distribution.as_ref(), bins, family.parameters(), alpha),
+                kolmogorov_smirnov: kolmogorov_smirnov(sample, distribution.as_ref(), alpha),
+                anderson_darling: (!family.is_discrete())
+                    .then(|| anderson_darling(sample, distribution.as_ref(), alpha)),
+                log_likelihood,
+                distribution,
+            })
+        })
+        .collect();
+    fits.sort_by(|a, b| {
+        a.kolmogorov_smirnov
+            .statistic
+            .partial_cmp(&b.kolmogorov_smirnov.statistic)
+            .unwrap()
+    });
+    fits
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Random::rngs::RandomGenerator;
+
+    use super::*;
+
+    fn draw(distribution: &dyn Distribution, n: usize, seed: i32) -> Vec<f64> {
+        let mut generator = RandomGenerator::with_seed(seed);
+        (0..n).map(|_| distribution.sample(&mut generator)).collect()
+    }
+
+    #[test]
+    fn test_estimates() {
+        let sample = draw(&Gamma::new(2.5, 1.5), 20000, 11);
+        for method in [Method::MaximumLikelihood, Method::Moments] {
+            let fitted = fit(Family::Gamma, &sample, method).unwrap();
+            assert!((fitted.mean() - 3.75).abs() < 0.1, "{:?}", method);
+            assert!((fitted.variance() - 5.625).abs() < 0.4, "{:?}", method);
+        }
+
+        let sample = draw(&Weibull::new(1.8, 3.0), 20000, 12);
+        for method in [Method::MaximumLikelihood, Method::Moments] {
+            let fitted = fit(Family::Weibull, &sample, method).unwrap();
+            let reference = Weibull::new(1.8, 3.0);
+            assert!((fitted.cdf(2.0) - reference.cdf(2.0)).abs() < 0.01, "{:?}", method);
+            assert!((fitted.cdf(5.0) - reference.cdf(5.0)).abs() < 0.01, "{:?}", method);
+        }
+
+        let sample = draw(&Beta::new(2.0, 5.0), 20000, 13);
+        let fitted = fit(Family::Beta, &sample, Method::MaximumLikelihood).unwrap();
+        assert!((fitted.mean() - 2.0 / 7.0).abs() < 0.005);
+        assert!((fitted.variance() - 10.0 / (49.0 * 8.0)).abs() < 0.002);
+
+        let sample = draw(&Pareto::new(3.0, 2.0), 20000, 14);
+        let fitted = fit(Family::Pareto, &sample, Method::MaximumLikelihood).unwrap();
+        assert!((fitted.mean() - 3.0).abs() < 0.05);
+
+        assert!(fit(Family::Exponential, &[-1.0, 2.0], Method::Moments).is_none());
+        assert!(fit(Family::Poisson, &[1.5, 2.0], Method::Moments).is_none());
+        assert!(fit(Family::Beta, &[0.5, 2.0], Method::Moments).is_none());
+        assert!((digamma(1.0) + 0.5772156649015329).abs() < 1e-10);
+        assert!((trigamma(1.0) - std::f64::consts::PI.powi(2) / 6.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_ranking() {
+        let sample = draw(&Lognormal::new(0.5, 0.4), 3000, 21);
+        let fits = rank(&sample, &Family::ALL, Method::MaximumLikelihood, 0.01);
+        assert_eq!(fits[0].family, Family::Lognormal);
+        assert!(fits[0].kolmogorov_smirnov.passed);
+        assert!(fits[0].anderson_darling.as_ref().unwrap().passed);
+        assert!(fits.iter().all(|f| !f.family.is_discrete() && f.family != Family::Beta));
+        assert!(!fits.iter().find(|f| f.family == Family::Exponential).unwrap().kolmogorov_smirnov.passed);
+
+        let sample = draw(&Poisson::new(4.0), 3000, 22);
+        let fits = rank(&sample, &[Family::Poisson, Family::Geometric], Method::MaximumLikelihood, 0.01);
+        assert_eq!(fits[0].family, Family::Poisson);
+        assert!(fits[0].chi_square.passed);
+        assert!(fits[0].aic() < fits[1].aic());
+    }
+}
@@ -0,0 +1,173 @@
+/* --------------------------------------------------------------------------
+ * Goodness-of-fit tests of a sample against any Distribution.
+ *
+ * chi-square          cells between the quantiles idf(j / k), merged until
+ *                     every cell expects at least 5 observations; the
+ *                     degrees of freedom drop by one per estimated parameter
+ * Kolmogorov-Smirnov  D = sup |F_n(x) - F(x)|
+ * Anderson-Darling    A^2 = -n - sum (2i - 1) [ln F(x(i)) + ln(1 - F(x(n+1-i)))] / n
+ *
+ * The KS and AD p-values use the asymptotic distributions for fully
+ * specified parameters (Marsaglia & Marsaglia's approximation for AD), so
+ * they are conservative when the parameters are estimated from the same
+ * sample. AD assumes a continuous distribution.
+ * --------------------------------------------------------------------------
+ */
+
+use super::{
+    rvms::{cdfChisquare, TINY},
+    Distributions::Distribution,
+    StreamTests::{ks_p_value, ks_statistic, TestOutcome},
+};
+
+fn sorted(sample: &[f64]) -> Vec<f64> {
+    let mut values = sample.to_vec();
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    values
+}
+
+/// Chi-square test with `bins` equiprobable cells, `estimated` parameters taken from the sample.
+pub fn chi_square(
+    sample: &[f64],
+    distribution: &dyn Distribution,
+    bins: usize,
+    estimated: usize,
+    alpha: f64,
+) -> TestOutcome {
+    let n = sample.len() as f64;
+    let mut bounds: Vec<f64> = (1..bins).map(|j| distribution.idf(j as f64 / bins as f64)).collect();
+    bounds.dedup();
+    bounds.push(f64::INFINITY);
+    let mut observed = vec![0.0; bounds.len()];
+    for x in sample {
+        observed[bounds.partition_point(|b| b < x)] += 1.0;
+    }
+    let mut expected = Vec::with_capacity(bounds.len());
+    let mut previous = 0.0;
+    for b in &bounds {
+        let cdf = if b.is_finite() { distribution.cdf(*b) } else { 1.0 };
+        expected.push(n * (cdf - previous));
+        previous = cdf;
+    }
+    // merge small cells into the next one, the last into the previous one
+    let (mut cells_o, mut cells_e) = (Vec::new(), Vec::new());
+    let (mut o, mut e) = (0.0, 0.0);
+    for (x, y) in observed.iter().zip(&expected) {
+        o += x;
+        e += y;
+        if e >= 5.0 {
+            cells_o.push(o);
+            cells_e.push(e);
+            o = 0.0;
+            e = 0.0;
+        }
+    }
+    if e > 0.0 || o > 0.0 {
+        match (cells_o.last_mut(), cells_e.last_mut()) {
+            (Some(lo), Some(le)) => {
+                *lo += o;
+                *le += e;
+            }
+            _ => {
+                cells_o.push(o);
+                cells_e.push(e);
+            }
+        }
+    }
+    let statistic = cells_o
+        .iter()
+        .zip(&cells_e)
+        .map(|(o, e)| (o - e) * (o - e) / e)
+        .sum::<f64>();
+    let df = cells_o.len() as i64 - 1 - estimated as i64;
+    if df < 1 {
+        return TestOutcome {
+            name: "chi-square",
+            statistic,
+            p_value: f64::NAN,
+            passed: false,
+        };
+    }
+    let p_value = 1.0 - cdfChisquare(df as u32, statistic);
+    TestOutcome {
+        name: "chi-square",
+        statistic,
+        p_value,
+        passed: p_value >= alpha,
+    }
+}
+
+pub fn kolmogorov_smirnov(sample: &[f64], distribution: &dyn Distribution, alpha: f64) -> TestOutcome {
+    let statistic = ks_statistic(&sorted(sample), |x| distribution.cdf(x));
+    let p_value = ks_p_value(statistic, sample.len());
+    TestOutcome {
+        name: "kolmogorov-smirnov",
+        statistic,
+        p_value,
+        passed: p_value >= alpha,
+    }
+}
+
+/// P(A^2 <= z) for large samples.
+fn anderson_darling_cdf(z: f64) -> f64 {
+    if z <= 0.0 {
+        0.0
+    } else if z < 2.0 {
+        (-1.2337141 / z).exp() / z.sqrt()
+            * (2.00012
+                + (0.247105 - (0.0649821 - (0.0347962 - (0.011672 - 0.00168691 * z) * z) * z) * z) * z)
+    } else {
+        (-(1.0776 - (2.30695 - (0.43424 - (0.082433 - (0.008056 - 0.0003146 * z) * z) * z) * z) * z).exp())
+            .exp()
+    }
+}
+
+pub fn anderson_darling(sample: &[f64], distribution: &dyn Distribution, alpha: f64) -> TestOutcome {
+    let values = sorted(sample);
+    let n = values.len();
+    let f: Vec<f64> = values
+        .iter()
+        .map(|x| distribution.cdf(*x).clamp(TINY, 1.0 - TINY))
+        .collect();
+    let sum: f64 = (0..n)
+        .map(|i| (2 * i + 1) as f64 * (f[i].ln() + (1.0 - f[n - 1 - i]).ln()))
+        .sum();
+    let statistic = -(n as f64) - sum / n as f64;
+    let p_value = (1.0 - anderson_darling_cdf(statistic)).clamp(0.0, 1.0);
+    TestOutcome {
+        name: "anderson-darling",
+        statistic,
+        p_value,
+        passed: p_value >= alpha,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Random::{
+        rngs::RandomGenerator,
+        Distributions::{Exponential, Normal, Uniform},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_gof() {
+        let mut generator = RandomGenerator::with_seed(777);
+        let exponential = Exponential::new(2.0);
+        let sample: Vec<f64> = (0..2000).map(|_| exponential.sample(&mut generator)).collect();
+
+        assert!(chi_square(&sample, &exponential, 20, 0, 0.01).passed);
+        assert!(kolmogorov_smirnov(&sample, &exponential, 0.01).passed);
+        assert!(anderson_darling(&sample, &exponential, 0.01).passed);
+
+        let wrong = Uniform::new(0.0, 12.0);
+        assert!(!chi_square(&sample, &wrong, 20, 0, 0.01).passed);
+        assert!(!kolmogorov_smirnov(&sample, &wrong, 0.01).passed);
+        let wrong = Normal::new(2.0, 2.0);
+        assert!(!anderson_darling(&sample, &wrong, 0.01).passed);
+
+        // 5% critical value of the asymptotic A^2 distribution
+        assert!((anderson_darling_cdf(2.492) - 0.95).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,247 @@
+/* --------------------------------------------------------------------------
+ * MRG32k3a combined multiple recursive generator of P. L'Ecuyer, with the
+ * stream and substream structure of RngStreams (period ~2^191):
+ *
+ *      x1(n) = (1403580 x1(n-2) - 810728 x1(n-3)) mod m1
+ *      x2(n) = (527612 x2(n-1) - 1370589 x2(n-3))  mod m2
+ *      u(n)  = ((x1(n) - x2(n)) mod m1) / (m1 + 1)
+ *
+ * Consecutive streams start 2^127 draws apart, each stream is split in
+ * substreams 2^76 draws apart. The jumps are products by the precomputed
+ * matrices A^(2^127) and A^(2^76) of the two recurrences.
+ * --------------------------------------------------------------------------
+ */
+
+use super::rngs::UniformSource;
+
+const M1: i64 = 4294967087;
+const M2: i64 = 4294944443;
+const A12: i64 = 1403580;
+const A13N: i64 = 810728;
+const A21: i64 = 527612;
+const A23N: i64 = 1370589;
+const NORM: f64 = 2.328306549295728e-10; /* 1 / (m1 + 1) */
+const DEFAULT: [i64; 6] = [12345; 6];
+
+const A1P76: [[i64; 3]; 3] = [
+    [82758667, 1871391091, 4127413238],
+    [3672831523, 69195019, 1871391091],
+    [3672091415, 3528743235, 69195019],
+];
+const A2P76: [[i64; 3]; 3] = [
+    [1511326704, 3759209742, 1610795712],
+    [4292754251, 1511326704, 3889917532],
+    [3859662829, 4292754251, 3708466080],
+];
+const A1P127: [[i64; 3]; 3] = [
+    [2427906178, 3580155704, 949770784],
+    [226153695, 1230515664, 3580155704],
+    [1988835001, 986791581, 1230515664],
+];
+const A2P127: [[i64; 3]; 3] = [
+    [1464411153, 277697599, 1610723613],
+    [32183930, 1464411153, 1022607788],
+    [2824425944, 32183930, 2093834863],
+];
+
+fn mat_vec_mod(a: &[[i64; 3]; 3], s: &[i64], m: i64) -> [i64; 3] {
+    let mut v = [0; 3];
+    for (i, row) in a.iter().enumerate() {
+        let sum: i128 = row.iter().zip(s).map(|(x, y)| *x as i128 * *y as i128).sum();
+        v[i] = (sum % m as i128) as i64;
+    }
+    v
+}
+
+fn jump(state: &[i64; 6], a1: &[[i64; 3]; 3], a2: &[[i64; 3]; 3]) -> [i64; 6] {
+    let x1 = mat_vec_mod(a1, &state[0..3], M1);
+    let x2 = mat_vec_mod(a2, &state[3..6], M2);
+    [x1[0], x1[1], x1[2], x2[0], x2[1], x2[2]]
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct StreamState {
+    start: [i64; 6],
+    substream: [i64; 6],
+    current: [i64; 6],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mrg32k3a {
+    states: Vec<StreamState>,
+    stream: usize,
+}
+
+impl Mrg32k3a {
+    /// Stream 0 starts from the RngStreams default seed, 12345 in all components.
+    pub fn new() -> Self {
+        Mrg32k3a::with_seed(DEFAULT)
+    }
+
+    /// The first three components must be below m1 and not all zero, the last
+    /// three below m2 and not all zero.
+    pub fn with_seed(seed: [i64; 6]) -> Self {
+        debug_assert!(seed[0..3].iter().all(|x| (0..M1).contains(x)), "Seeds 0..3 should be below m1");
+        debug_assert!(seed[3..6].iter().all(|x| (0..M2).contains(x)), "Seeds 3..6 should be below m2");
+        debug_assert!(seed[0..3].iter().any(|x| *x != 0), "Seeds 0..3 should not be all zero");
+        debug_assert!(seed[3..6].iter().any(|x| *x != 0), "Seeds 3..6 should not be all zero");
+        Mrg32k3a {
+            states: vec![StreamState {
+                start: seed,
+                substream: seed,
+                current: seed,
+            }],
+            stream: 0,
+        }
+    }
+
+    /// Moves the selected stream to the start of its next substream.
+    pub fn next_substream(&mut self) {
+        let state = &mut self.states[self.stream];
+        state.substream = jump(&state.substream, &A1P76, &A2P76);
+        state.current = state.substream;
+    }
+
+    /// Moves the selected stream back to the start of its current substream.
+    pub fn reset_substream(&mut self) {
+        let state = &mut self.states[self.stream];
+        state.current = state.substream;
+    }
+
+    /// Moves the selected stream back to its first substream.
+    pub fn reset_stream(&mut self) {
+        let state = &mut self.states[self.stream];
+        state.substream = state.start;
+        state.current = state.start;
+    }
+
+    /// Start of stream `index` when stream 0 starts from `seed`.
+    fn stream_start(seed: [i64; 6], index: usize) -> [i64; 6] {
+        (0..index).fold(seed, |start, _| jump(&start, &A1P127, &A2P127))
+    }
+
+    /// Current state of the selected stream.
+    pub fn state(&self) -> [i64; 6] {
+        self.states[self.stream].current
+    }
+}
+
+impl Default for Mrg32k3a {
+    fn default() -> Self {
+        Mrg32k3a::new()
+    }
+}
+
+impl UniformSource for Mrg32k3a {
+    fn Random(&mut self) -> f64 {
+        let s = &mut self.states[self.stream].current;
+        let p1 = (A12 * s[1] - A13N * s[0]).rem_euclid(M1);
+        s[0] = s[1];
+        s[1] = s[2];
+        s[2] = p1;
+        let p2 = (A21 * s[5] - A23N * s[3]).rem_euclid(M2);
+        s[3] = s[4];
+        s[4] = s[5];
+        s[5] = p2;
+        if p1 > p2 {
+            (p1 - p2) as f64 * NORM
+        } else {
+            (p1 - p2 + M1) as f64 * NORM
+        }
+    }
+
+    /// Streams are created on demand, the bound is only nominal.
+    fn streams(&self) -> usize {
+        usize::MAX
+    }
+
+    fn SelectStream(&mut self, index: usize) {
+        while self.states.len() <= index {
+            let start = jump(&self.states[self.states.len() - 1].start, &A1P127, &A2P127);
+            self.states.push(StreamState {
+                start,
+                substream: start,
+                current: start,
+            });
+        }
+        self.stream = index;
+    }
+
+    fn selected(&self) -> usize {
+        self.stream
+    }
+
+    /// Stream 0 restarts from `seed mod m2` in all components, the default seed if that is 0.
+    fn restart(&mut self, stream: usize, seed: u64) {
+        let component = match (seed % M2 as u64) as i64 {
+            0 => DEFAULT[0],
+            x => x,
+        };
+        let selected = self.stream;
+        self.SelectStream(stream);
+        let start = Mrg32k3a::stream_start([component; 6], stream);
+        self.states[stream] = StreamState {
+            start,
+            substream: start,
+            current: start,
+        };
+        self.stream = selected;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streams_and_substreams() {
+        let mut generator = Mrg32k3a::new();
+        assert!((generator.Random() - 0.12701112204657714).abs() < 1e-15);
+        assert!((generator.Random() - 0.3185275653967945).abs() < 1e-15);
+
+        // one jump of 2^76 equals 2^76 steps of the recurrences: check the
+        // matrices against squaring the one step matrices 76 times
+        let mut a1 = [[0, 1, 0], [0, 0, 1], [M1 - A13N, A12, 0]];
+        let mut a2 = [[0, 1, 0], [0, 0, 1], [M2 - A23N, 0, A21]];
+        let square = |a: &[[i64; 3]; 3], m: i64| {
+            let mut r = [[0; 3]; 3];
+            for (i, row) in r.iter_mut().enumerate() {
+                for (j, x) in row.iter_mut().enumerate() {
+                    *x = ((0..3).map(|k| a[i][k] as i128 * a[k][j] as i128).sum::<i128>() % m as i128) as i64;
+                }
+            }
+            r
+        };
+        for e in 1..=127 {
+            a1 = square(&a1, M1);
+            a2 = square(&a2, M2);
+            if e == 76 {
+                assert_eq!((a1, a2), (A1P76, A2P76));
+            }
+        }
+        assert_eq!((a1, a2), (A1P127, A2P127));
+
+        generator.SelectStream(3);
+        let first: Vec<f64> = (0..10).map(|_| generator.Random()).collect();
+        generator.next_substream();
+        let second: Vec<f64> = (0..10).map(|_| generator.Random()).collect();
+        assert_ne!(first, second);
+        generator.reset_substream();
+        assert_eq!(second, (0..10).map(|_| generator.Random()).collect::<Vec<f64>>());
+        generator.reset_stream();
+        assert_eq!(first, (0..10).map(|_| generator.Random()).collect::<Vec<f64>>());
+        generator.SelectStream(2);
+        assert_ne!(first[0], generator.Random());
+
+        // restarting a stream puts it where seeding would, the selection is kept
+        generator.restart(5, 777);
+        assert_eq!(generator.selected(), 2);
+        generator.SelectStream(5);
+        let mut seeded = Mrg32k3a::with_seed([777; 6]);
+        seeded.SelectStream(5);
+        assert_eq!(generator.state(), seeded.state());
+        generator.restart(3, 12345);
+        generator.SelectStream(3);
+        assert_eq!(first, (0..10).map(|_| generator.Random()).collect::<Vec<f64>>());
+    }
+}
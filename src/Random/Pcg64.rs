@@ -0,0 +1,104 @@
+/* --------------------------------------------------------------------------
+ * PCG64 (XSL RR 128/64) of M. E. O'Neill: a 128 bit linear congruential
+ * state whose high and low halves are xor-ed and randomly rotated into a
+ * 64 bit output. Period 2^128 for each of the 2^127 increments, stream i
+ * uses the increment 2 i + 1, as pcg64_srandom_r(seed, i) in pcg-c.
+ * --------------------------------------------------------------------------
+ */
+
+use super::rngs::UniformSource;
+
+const MULTIPLIER: u128 = 0x2360_ED05_1FC6_5DA4_4385_DF64_9FCC_F645;
+
+/// Maps 64 random bits to the open interval (0, 1).
+pub(crate) fn to_open_unit(x: u64) -> f64 {
+    ((x >> 12) as f64 + 0.5) * (1.0 / (1u64 << 52) as f64)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pcg64 {
+    seed: u128,
+    states: Vec<u128>,
+    stream: usize,
+}
+
+impl Pcg64 {
+    pub fn new(seed: u128) -> Self {
+        Pcg64 {
+            seed,
+            states: vec![Pcg64::initial(seed, 0)],
+            stream: 0,
+        }
+    }
+
+    fn increment(stream: usize) -> u128 {
+        ((stream as u128) << 1) | 1
+    }
+
+    fn initial(seed: u128, stream: usize) -> u128 {
+        let inc = Pcg64::increment(stream);
+        let state = inc;
+        (state.wrapping_add(seed)).wrapping_mul(MULTIPLIER).wrapping_add(inc)
+    }
+
+    /// Next 64 random bits of the selected stream.
+    pub fn next_u64(&mut self) -> u64 {
+        let state = &mut self.states[self.stream];
+        *state = state.wrapping_mul(MULTIPLIER).wrapping_add(Pcg64::increment(self.stream));
+        let x = ((*state >> 64) as u64) ^ (*state as u64);
+        x.rotate_right((*state >> 122) as u32)
+    }
+}
+
+impl UniformSource for Pcg64 {
+    fn Random(&mut self) -> f64 {
+        to_open_unit(self.next_u64())
+    }
+
+    /// Streams are created on demand, the bound is only nominal.
+    fn streams(&self) -> usize {
+        usize::MAX
+    }
+
+    fn SelectStream(&mut self, index: usize) {
+        while self.states.len() <= index {
+            self.states.push(Pcg64::initial(self.seed, self.states.len()));
+        }
+        self.stream = index;
+    }
+
+    fn selected(&self) -> usize {
+        self.stream
+    }
+
+    fn restart(&mut self, stream: usize, seed: u64) {
+        let selected = self.stream;
+        self.SelectStream(stream);
+        self.states[stream] = Pcg64::initial(seed as u128, stream);
+        self.stream = selected;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_output() {
+        // pcg64 demo of pcg-c: seed 42, sequence 54
+        let mut generator = Pcg64::new(42);
+        generator.SelectStream(54);
+        assert_eq!(generator.next_u64(), 0x86b1da1d72062b68);
+        assert_eq!(generator.next_u64(), 0x1304aa46c9853d39);
+        assert_eq!(generator.next_u64(), 0xa3670e9e0dd50358);
+
+        generator.SelectStream(0);
+        let mean = (0..100000).map(|_| generator.Random()).sum::<f64>() / 100000.0;
+        assert!((mean - 0.5).abs() < 0.005, "mean {}", mean);
+        assert!(to_open_unit(0) > 0.0 && to_open_unit(u64::MAX) < 1.0);
+
+        generator.restart(54, 42);
+        generator.SelectStream(54);
+        assert_eq!(generator.next_u64(), 0x86b1da1d72062b68);
+    }
+}
@@ -0,0 +1,247 @@
+/* --------------------------------------------------------------------------
+ * Phase-type distributions: the time to absorption of a continuous time
+ * Markov chain with transient phases, given by an initial probability row
+ * vector alpha and a sub-generator matrix S. The exit rates are s0 = -S 1.
+ *
+ *      pdf(x)  = alpha exp(S x) s0
+ *      cdf(x)  = 1 - alpha exp(S x) 1
+ *      E[X^k]  = k! alpha (-S)^-k 1
+ *
+ * Hyperexponential, hypoexponential, Erlang and Coxian distributions are
+ * built as special cases, and fit() matches a target mean and squared
+ * coefficient of variation with an H2 (balanced means) or an Erlang mix.
+ * --------------------------------------------------------------------------
+ */
+
+use crate::Collections::Matrix::Matrix;
+
+use super::{rvgs::Variates, rvms::TINY, Distributions::Distribution};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhaseType {
+    alpha: Vec<f64>,
+    generator: Matrix<f64>,
+    exit: Vec<f64>,
+}
+
+impl PhaseType {
+    /// `alpha` may sum to less than one, the remaining mass being an atom at zero.
+    pub fn new(alpha: Vec<f64>, generator: Matrix<f64>) -> Self {
+        debug_assert!(generator.is_square() && generator.rows() == alpha.len(), "Dimension mismatch");
+        debug_assert!(alpha.iter().all(|a| *a >= 0.0), "Initial probabilities should not be negative");
+        debug_assert!(alpha.iter().sum::<f64>() <= 1.0 + 1e-9, "Initial probabilities should not exceed 1");
+        let exit = generator.row_sums().iter().map(|s| -s).collect::<Vec<f64>>();
+        debug_assert!(exit.iter().all(|s| *s >= -1e-9), "Rows of the sub-generator should not sum above zero");
+        PhaseType {
+            alpha,
+            generator,
+            exit,
+        }
+    }
+
+    /// Exponential phase `i` with rate `rates[i]` chosen with probability `probabilities[i]`.
+    pub fn hyperexponential(probabilities: Vec<f64>, rates: Vec<f64>) -> Self {
+        debug_assert!(probabilities.len() == rates.len(), "One rate per branch is required");
+        let generator = Matrix::diagonal(&rates.iter().map(|r| -r).collect::<Vec<f64>>());
+        PhaseType::new(probabilities, generator)
+    }
+
+    /// Sum of exponential phases with the given rates, crossed in order.
+    pub fn hypoexponential(rates: Vec<f64>) -> Self {
+        let continuations = vec![1.0; rates.len().saturating_sub(1)];
+        PhaseType::coxian(rates, continuations)
+    }
+
+    pub fn erlang(k: usize, rate: f64) -> Self {
+        PhaseType::hypoexponential(vec![rate; k])
+    }
+
+    /// Phase `i` has rate `rates[i]` and moves on to phase `i + 1` with probability
+    /// `continuations[i]`, leaving otherwise.
+    pub fn coxian(rates: Vec<f64>, continuations: Vec<f64>) -> Self {
+        debug_assert!(!rates.is_empty(), "At least one phase is required");
+        debug_assert!(continuations.len() + 1 == rates.len(), "One continuation per non final phase is required");
+        let n = rates.len();
+        let mut generator = Matrix::zeros(n, n);
+        for i in 0..n {
+            generator[(i, i)] = -rates[i];
+            if i + 1 < n {
+                generator[(i, i + 1)] = rates[i] * continuations[i];
+            }
+        }
+        let mut alpha = vec![0.0; n];
+        alpha[0] = 1.0;
+        PhaseType::new(alpha, generator)
+    }
+
+    /// Two-moment fit: a balanced-means H2 for `scv > 1`, an exponential for `scv = 1`
+    /// and a mix of Erlang(k-1) and Erlang(k) with a common rate for `scv < 1`.
+    pub fn fit(mean: f64, scv: f64) -> Self {
+        debug_assert!(mean > 0.0 && scv > 0.0, "Use mean > 0.0 and scv > 0.0");
+        if (scv - 1.0).abs() < TINY {
+            PhaseType::erlang(1, 1.0 / mean)
+        } else if scv > 1.0 {
+            let p = 0.5 * (1.0 + ((scv - 1.0) / (scv + 1.0)).sqrt());
+            PhaseType::hyperexponential(vec![p, 1.0 - p], vec![2.0 * p / mean, 2.0 * (1.0 - p) / mean])
+        } else {
+            let k = (1.0 / scv).ceil().max(2.0);
+            let p = (k * scv - (k * (1.0 + scv) - k * k * scv).sqrt()) / (1.0 + scv);
+            let rate = (k - p) / mean;
+            let n = k as usize;
+            let mut fitted = PhaseType::erlang(n, rate);
+            // starting from the second phase skips one stage: Erlang(k - 1) with probability p
+            fitted.alpha = vec![0.0; n];
+            fitted.alpha[0] = 1.0 - p;
+            fitted.alpha[1] = p;
+            fitted
+        }
+    }
+
+    pub fn phases(&self) -> usize {
+        self.alpha.len()
+    }
+
+    pub fn alpha(&self) -> &[f64] {
+        &self.alpha
+    }
+
+    pub fn generator(&self) -> &Matrix<f64> {
+        &self.generator
+    }
+
+    pub fn exit_rates(&self) -> &[f64] {
+        &self.exit
+    }
+
+    /// E[X^k] = k! alpha (-S)^-k 1
+    pub fn moment(&self, k: u32) -> f64 {
+        let m = self
+            .generator
+            .scale(-1.0)
+            .inverse()
+            .expect("Sub-generator of a phase-type distribution should be invertible");
+        let mut v = self.alpha.clone();
+        for _ in 0..k {
+            v = m.left_mul(&v);
+        }
+        let factorial: f64 = (1..=k).map(|i| i as f64).product();
+        factorial * v.iter().sum::<f64>()
+    }
+
+    /// Squared coefficient of variation.
+    pub fn scv(&self) -> f64 {
+        let m = self.moment(1);
+        self.moment(2) / (m * m) - 1.0
+    }
+}
+
+impl Distribution for PhaseType {
+    fn sample(&self, rng: &mut dyn Variates) -> f64 {
+        let mut u = rng.Random();
+        let mut phase = None;
+        for (i, a) in self.alpha.iter().enumerate() {
+            if u < *a {
+                phase = Some(i);
+                break;
+            }
+            u -= a;
+        }
+        let mut time = 0.0;
+        while let Some(i) = phase {
+            let rate = -self.generator[(i, i)];
+            time += rng.Exponential(1.0 / rate);
+            let mut u = rng.Random() * rate;
+            phase = None;
+            for j in (0..self.phases()).filter(|j| *j != i) {
+                if u < self.generator[(i, j)] {
+                    phase = Some(j);
+                    break;
+                }
+                u -= self.generator[(i, j)];
+            }
+        }
+        time
+    }
+    fn pdf(&self, x: f64) -> f64 {
+        if x < 0.0 {
+            return 0.0;
+        }
+        let v = self.generator.scale(x).exp().left_mul(&self.alpha);
+        v.iter().zip(&self.exit).map(|(a, b)| a * b).sum()
+    }
+    fn cdf(&self, x: f64) -> f64 {
+        if x < 0.0 {
+            return 0.0;
+        }
+        1.0 - self.generator.scale(x).exp().left_mul(&self.alpha).iter().sum::<f64>()
+    }
+    /// Bisection on the cdf.
+    fn idf(&self, u: f64) -> f64 {
+        let (mut low, mut high) = (0.0, self.mean().max(TINY));
+        while self.cdf(high) < u {
+            low = high;
+            high *= 2.0;
+        }
+        while high - low > TINY * high.max(1.0) {
+            let mid = 0.5 * (low + high);
+            if self.cdf(mid) < u {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        0.5 * (low + high)
+    }
+    fn mean(&self) -> f64 {
+        self.moment(1)
+    }
+    fn variance(&self) -> f64 {
+        let m = self.moment(1);
+        self.moment(2) - m * m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Random::{rngs::RandomGenerator, Distributions::Erlang};
+
+    use super::*;
+
+    #[test]
+    fn test_special_cases() {
+        let erlang = PhaseType::erlang(3, 2.0);
+        let reference = Erlang::new(3, 0.5);
+        assert!((erlang.mean() - reference.mean()).abs() < 1e-9);
+        assert!((erlang.variance() - reference.variance()).abs() < 1e-9);
+        for x in [0.3, 1.0, 2.5] {
+            assert!((erlang.cdf(x) - reference.cdf(x)).abs() < 1e-8);
+            assert!((erlang.pdf(x) - reference.pdf(x)).abs() < 1e-8);
+        }
+        assert!((erlang.idf(0.4) - reference.idf(0.4)).abs() < 1e-6);
+
+        let hyper = PhaseType::hyperexponential(vec![0.3, 0.7], vec![1.0, 4.0]);
+        assert!((hyper.mean() - (0.3 + 0.7 / 4.0)).abs() < 1e-9);
+        assert!((hyper.cdf(1.0) - (1.0 - 0.3 * (-1.0f64).exp() - 0.7 * (-4.0f64).exp())).abs() < 1e-9);
+
+        let hypo = PhaseType::hypoexponential(vec![1.0, 3.0]);
+        assert!((hypo.mean() - (1.0 + 1.0 / 3.0)).abs() < 1e-9);
+        assert!((hypo.variance() - (1.0 + 1.0 / 9.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fit_and_sample() {
+        let mut generator = RandomGenerator::new();
+        generator.PutSeed(24680);
+        for scv in [0.3, 0.5, 1.0, 4.0] {
+            let fitted = PhaseType::fit(2.0, scv);
+            assert!((fitted.mean() - 2.0).abs() < 1e-9, "mean for scv {}", scv);
+            assert!((fitted.scv() - scv).abs() < 1e-9, "scv {} fitted {}", scv, fitted.scv());
+            let n = 20000;
+            let samples: Vec<f64> = (0..n).map(|_| fitted.sample(&mut generator)).collect();
+            let mean = samples.iter().sum::<f64>() / n as f64;
+            let variance = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1) as f64;
+            assert!((mean - 2.0).abs() < 0.1, "sample mean {} for scv {}", mean, scv);
+            assert!((variance / (mean * mean) - scv).abs() < 0.15 * scv.max(1.0), "sample scv for {}", scv);
+        }
+    }
+}
@@ -1,27 +1,56 @@
-use super::{rngs::RandomGenerator, rvgs::Uniform};
+use super::{
+    rngs::{with_global_source, UniformSource},
+    rvgs::Uniform,
+};
 use std::process::Output;
 
-struct RandomVariable<'generator> {
+/// Generator given by the caller, or the global source reached on every draw.
+enum Generator<'generator> {
+    Borrowed(&'generator mut dyn UniformSource),
+    Global,
+}
+
+impl Generator<'_> {
+    fn SelectStream(&mut self, stream: usize) {
+        match self {
+            Generator::Borrowed(generator) => generator.SelectStream(stream),
+            Generator::Global => with_global_source(|global| global.SelectStream(stream)),
+        }
+    }
+
+    fn Random(&mut self) -> f64 {
+        match self {
+            Generator::Borrowed(generator) => generator.Random(),
+            Generator::Global => with_global_source(|global| global.Random()),
+        }
+    }
+}
+
+pub struct RandomVariable<'generator> {
     stream: usize,
-    generator: &'generator mut RandomGenerator,
+    generator: Generator<'generator>,
     func: &'generator dyn Fn() -> f64,
 }
 
 impl<'generator> RandomVariable<'generator> {
     pub fn new(
         stream: usize,
-        generator: &'generator mut RandomGenerator,
+        generator: &'generator mut dyn UniformSource,
         func: &'generator dyn Fn() -> f64,
     ) -> Self {
         RandomVariable {
             stream,
-            generator,
+            generator: Generator::Borrowed(generator),
             func,
         }
     }
 
     pub fn from_static_generator(stream: usize, func: &'generator dyn Fn() -> f64) -> Self {
-        RandomVariable::new(stream, RandomGenerator::Global(), func)
+        RandomVariable {
+            stream,
+            generator: Generator::Global,
+            func,
+        }
     }
 
     pub fn call(&mut self) -> f64 {
@@ -30,52 +59,138 @@ impl<'generator> RandomVariable<'generator> {
     }
 }
 
-fn selector(weights: &Vec<f64>, generator: &mut RandomGenerator)-> usize{
-    
-    let mut a  = vec![0.0;weights.len()];
-    a[0] =  weights[0];
-    for i in 1..a.len(){
-        a[i] = a[i-1]+  weights[i];
+/// Tolerance allowed on the sum of probabilities passed to a `Chooser`.
+pub const WEIGHT_TOLERANCE: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WeightError {
+    Empty,
+    /// Index of the first negative or non finite weight.
+    Negative(usize),
+    ZeroSum,
+    /// Probabilities whose sum is farther than `WEIGHT_TOLERANCE` from 1.
+    NotNormalized(f64),
+}
+
+impl std::fmt::Display for WeightError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WeightError::Empty => write!(f, "no weights given"),
+            WeightError::Negative(i) => write!(f, "weight {} is negative or not finite", i),
+            WeightError::ZeroSum => write!(f, "weights sum to zero"),
+            WeightError::NotNormalized(sum) => write!(f, "probabilities sum to {} instead of 1", sum),
+        }
+    }
+}
+
+impl std::error::Error for WeightError {}
+
+/// Walker's alias table: after an O(n) setup every draw costs one uniform and
+/// one comparison. Weights are normalized by their sum.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AliasTable {
+    probability: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl AliasTable {
+    pub fn new(weights: &[f64]) -> Result<Self, WeightError> {
+        if weights.is_empty() {
+            return Err(WeightError::Empty);
+        }
+        if let Some(i) = weights.iter().position(|w| !(w.is_finite() && *w >= 0.0)) {
+            return Err(WeightError::Negative(i));
+        }
+        let sum: f64 = weights.iter().sum();
+        if sum <= 0.0 {
+            return Err(WeightError::ZeroSum);
+        }
+        let n = weights.len();
+        let mut probability: Vec<f64> = weights.iter().map(|w| w * n as f64 / sum).collect();
+        let mut alias: Vec<usize> = (0..n).collect();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) = (0..n).partition(|i| probability[*i] < 1.0);
+        while let (Some(s), Some(l)) = (small.pop(), large.pop()) {
+            alias[s] = l;
+            probability[l] -= 1.0 - probability[s];
+            if probability[l] < 1.0 {
+                small.push(l);
+            } else {
+                large.push(l);
+            }
+        }
+        // leftovers only differ from 1 by rounding
+        for i in small.into_iter().chain(large) {
+            probability[i] = 1.0;
+        }
+        Ok(AliasTable { probability, alias })
     }
-    let y =  generator.Random();
-    let mut r = 0;
-    while y>= a[r]{
-        r+=1;
+
+    pub fn len(&self) -> usize {
+        self.probability.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.probability.is_empty()
+    }
+
+    /// Index selected by the uniform draw `u` in [0, 1).
+    pub fn sample(&self, u: f64) -> usize {
+        let scaled = u * self.len() as f64;
+        let i = (scaled as usize).min(self.len() - 1);
+        if scaled - (i as f64) < self.probability[i] {
+            i
+        } else {
+            self.alias[i]
+        }
     }
-    r
 }
 
-struct Chooser<'generator, T> {
+/// Calls one of `func`, chosen with the given probabilities.
+pub struct Chooser<'generator, T> {
     stream: usize,
-    generator: &'generator mut RandomGenerator,
-    weights: Vec<f64>,
+    generator: Generator<'generator>,
+    table: AliasTable,
     func: Vec<&'generator dyn Fn() -> T>,
 }
 
 impl<'generator, T> Chooser<'generator, T> {
     pub fn new(
         stream: usize,
-        generator: &'generator mut RandomGenerator,
+        generator: &'generator mut dyn UniformSource,
         weights: Vec<f64>,
         func: Vec<&'generator dyn Fn() -> T>,
-    ) -> Self {
-        debug_assert!(weights.iter().sum::<f64>() == 1.0, "Sum of weights should be 1");
-        Chooser {
+    ) -> Result<Self, WeightError> {
+        Chooser::with_generator(stream, Generator::Borrowed(generator), weights, func)
+    }
+
+    fn with_generator(
+        stream: usize,
+        generator: Generator<'generator>,
+        weights: Vec<f64>,
+        func: Vec<&'generator dyn Fn() -> T>,
+    ) -> Result<Self, WeightError> {
+        debug_assert!(weights.len() == func.len(), "One weight per function is required");
+        let table = AliasTable::new(&weights)?;
+        let sum = weights.iter().sum::<f64>();
+        if (sum - 1.0).abs() > WEIGHT_TOLERANCE {
+            return Err(WeightError::NotNormalized(sum));
+        }
+        Ok(Chooser {
             stream,
             generator,
-            weights,
+            table,
             func,
-        }
+        })
     }
 
 
-    pub fn from_static_generator(stream: usize, weights: Vec<f64>,func: Vec<&'generator dyn Fn() -> T>)->Self{
-        Chooser::new(stream, RandomGenerator::Global(), weights, func)
+    pub fn from_static_generator(stream: usize, weights: Vec<f64>,func: Vec<&'generator dyn Fn() -> T>)->Result<Self, WeightError>{
+        Chooser::with_generator(stream, Generator::Global, weights, func)
     }
 
     pub fn call(&mut self) -> T{
         self.generator.SelectStream(self.stream);
-        let selected = selector(&self.weights, &mut self.generator);
+        let selected = self.table.sample(self.generator.Random());
         self.func[selected]()
     }
     
@@ -99,7 +214,7 @@ mod tests {
 
     #[test]
     fn test_selector(){
-        let mut chooser = Chooser::<u32>::from_static_generator(1, vec![0.3,0.7], vec![&||{0},&||{1}]);
+        let mut chooser = Chooser::<u32>::from_static_generator(1, vec![0.3,0.7], vec![&||{0},&||{1}]).unwrap();
         let mut a:[u32;2]= [0;2];        
         for _i in 0..100{
             a[chooser.call() as usize] += 1;
@@ -107,4 +222,19 @@ mod tests {
         println!("{:?}",a);
 
     }
+
+    #[test]
+    fn test_alias_table(){
+        let table = AliasTable::new(&[1.0, 0.0, 3.0, 4.0]).unwrap();
+        let mut counts = [0usize; 4];
+        for i in 0..8000 {
+            counts[table.sample((i as f64 + 0.5) / 8000.0)] += 1;
+        }
+        assert_eq!(counts, [1000, 0, 3000, 4000]);
+        assert_eq!(AliasTable::new(&[]), Err(WeightError::Empty));
+        assert_eq!(AliasTable::new(&[0.5, -0.1]), Err(WeightError::Negative(1)));
+        assert_eq!(AliasTable::new(&[0.0, 0.0]), Err(WeightError::ZeroSum));
+        assert!(Chooser::<u32>::from_static_generator(1, vec![0.3, 0.3], vec![&||{0},&||{1}]).is_err());
+        assert!(Chooser::<u32>::from_static_generator(1, vec![0.1; 10], vec![&||{0}; 10]).is_ok());
+    }
 }
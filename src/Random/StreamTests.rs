@@ -0,0 +1,299 @@
+/* --------------------------------------------------------------------------
+ * Empirical tests of uniform random number streams, in the form given by
+ * Park & Leemis (Discrete-Event Simulation: A First Course, ch. 10).
+ *
+ *  test          categories                          degrees of freedom
+ *  uniformity    k equal bins of (0, 1)               k - 1
+ *  serial        d x d bins of non overlapping pairs  d^2 - 1
+ *  runs-up       run lengths 1 .. k-1 and >= k        k - 1
+ *  gap           gaps 0 .. r-1 and >= r in [a, b)     r
+ *
+ * The chi-square tests pass when the statistic lies between the alpha/2
+ * and 1 - alpha/2 quantiles of the chi-square distribution, so that too
+ * good a fit is rejected as well. Kolmogorov-Smirnov uses the asymptotic
+ * Kolmogorov distribution with Stephens' correction, and the mean test a
+ * Student t statistic against 0.5.
+ * --------------------------------------------------------------------------
+ */
+
+use super::{
+    rngs::UniformSource,
+    rvms::{cdfChisquare, cdfStudent, idfChisquare},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestOutcome {
+    pub name: &'static str,
+    pub statistic: f64,
+    pub p_value: f64,
+    pub passed: bool,
+}
+
+/// Outcome of every test for one stream.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamReport {
+    pub stream: usize,
+    pub outcomes: Vec<TestOutcome>,
+}
+
+impl StreamReport {
+    pub fn passed(&self) -> bool {
+        self.outcomes.iter().all(|o| o.passed)
+    }
+
+    pub fn failures(&self) -> Vec<&'static str> {
+        self.outcomes.iter().filter(|o| !o.passed).map(|o| o.name).collect()
+    }
+}
+
+fn chi_square(name: &'static str, observed: &[f64], expected: &[f64], alpha: f64) -> TestOutcome {
+    let statistic = observed
+        .iter()
+        .zip(expected)
+        .map(|(o, e)| (o - e) * (o - e) / e)
+        .sum::<f64>();
+    let df = (observed.len() - 1) as u32;
+    let (low, high) = (idfChisquare(df, 0.5 * alpha), idfChisquare(df, 1.0 - 0.5 * alpha));
+    TestOutcome {
+        name,
+        statistic,
+        p_value: 1.0 - cdfChisquare(df, statistic),
+        passed: low <= statistic && statistic <= high,
+    }
+}
+
+/// Chi-square test of `n` draws over `bins` equal bins, use n >= 10 bins.
+pub fn uniformity(source: &mut dyn UniformSource, n: usize, bins: usize, alpha: f64) -> TestOutcome {
+    let mut observed = vec![0.0; bins];
+    for _ in 0..n {
+        observed[((source.Random() * bins as f64) as usize).min(bins - 1)] += 1.0;
+    }
+    chi_square("uniformity", &observed, &vec![n as f64 / bins as f64; bins], alpha)
+}
+
+/// Chi-square test of `n` non overlapping pairs over `d` x `d` bins, use n >= 10 d^2.
+pub fn serial(source: &mut dyn UniformSource, n: usize, d: usize, alpha: f64) -> TestOutcome {
+    let mut observed = vec![0.0; d * d];
+    let bin = |u: f64| ((u * d as f64) as usize).min(d - 1);
+    for _ in 0..n {
+        let (x, y) = (bin(source.Random()), bin(source.Random()));
+        observed[x * d + y] += 1.0;
+    }
+    chi_square("serial", &observed, &vec![n as f64 / (d * d) as f64; d * d], alpha)
+}
+
+/// Chi-square test of `n` ascending runs, lengths `k` and longer pooled. The value
+/// ending a run is discarded, which makes consecutive runs independent with
+/// P(length = r) = r / (r + 1)!.
+pub fn runs_up(source: &mut dyn UniformSource, n: usize, k: usize, alpha: f64) -> TestOutcome {
+    let mut observed = vec![0.0; k];
+    for _ in 0..n {
+        let mut length = 1;
+        let mut previous = source.Random();
+        loop {
+            let u = source.Random();
+            if u <= previous {
+                break;
+            }
+            length += 1;
+            previous = u;
+        }
+        observed[length.min(k) - 1] += 1.0;
+    }
+    let mut factorial = 1.0;
+    let mut expected = Vec::with_capacity(k);
+    for r in 1..k {
+        factorial *= (r + 1) as f64;
+        expected.push(n as f64 * r as f64 / factorial);
+    }
+    // P(length >= k) = 1 / k!
+    expected.push(n as f64 / factorial);
+    chi_square("runs-up", &observed, &expected, alpha)
+}
+
+/// Chi-square test of `n` gaps between draws falling in [a, b), gaps of `r` and more pooled.
+pub fn gap(source: &mut dyn UniformSource, n: usize, a: f64, b: f64, r: usize, alpha: f64) -> TestOutcome {
+    debug_assert!(0.0 <= a && a < b && b <= 1.0, "Use 0.0 <= a < b <= 1.0");
+    let mut observed = vec![0.0; r + 1];
+    for _ in 0..n {
+        let mut length = 0;
+        loop {
+            let u = source.Random();
+            if a <= u && u < b {
+                break;
+            }
+            length += 1;
+        }
+        observed[length.min(r)] += 1.0;
+    }
+    let p = b - a;
+    let mut expected: Vec<f64> = (0..r).map(|g| n as f64 * p * (1.0 - p).powi(g as i32)).collect();
+    expected.push(n as f64 * (1.0 - p).powi(r as i32));
+    chi_square("gap", &observed, &expected, alpha)
+}
+
+/// P(K > t) for the Kolmogorov distribution.
+fn kolmogorov_tail(t: f64) -> f64 {
+    if t < 0.2 {
+        return 1.0;
+    }
+    let sum: f64 = (1..=100)
+        .map(|k| {
+            let k = k as f64;
+            let sign = if k as u32 % 2 == 1 { 1.0 } else { -1.0 };
+            sign * (-2.0 * k * k * t * t).exp()
+        })
+        .sum();
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+/// Kolmogorov-Smirnov test of `n` draws against the Uniform(0, 1) cdf.
+pub fn kolmogorov_smirnov(source: &mut dyn UniformSource, n: usize, alpha: f64) -> TestOutcome {
+    let mut samples: Vec<f64> = (0..n).map(|_| source.Random()).collect();
+    samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let statistic = ks_statistic(&samples, |x| x);
+    let p_value = ks_p_value(statistic, n);
+    TestOutcome {
+        name: "kolmogorov-smirnov",
+        statistic,
+        p_value,
+        passed: p_value >= alpha,
+    }
+}
+
+/// sup |F_n(x) - F(x)| of a sorted sample.
+pub(crate) fn ks_statistic(sorted: &[f64], cdf: impl Fn(f64) -> f64) -> f64 {
+    let n = sorted.len() as f64;
+    sorted
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let f = cdf(*x);
+            ((i + 1) as f64 / n - f).max(f - i as f64 / n)
+        })
+        .fold(0.0, f64::max)
+}
+
+pub(crate) fn ks_p_value(statistic: f64, n: usize) -> f64 {
+    let root = (n as f64).sqrt();
+    kolmogorov_tail(statistic * (root + 0.12 + 0.11 / root))
+}
+
+/// Two sided Student t test of the mean of `n` draws against 0.5.
+pub fn mean(source: &mut dyn UniformSource, n: usize, alpha: f64) -> TestOutcome {
+    let samples: Vec<f64> = (0..n).map(|_| source.Random()).collect();
+    let m = samples.iter().sum::<f64>() / n as f64;
+    let s = (samples.iter().map(|x| (x - m) * (x - m)).sum::<f64>() / (n - 1) as f64).sqrt();
+    let statistic = (m - 0.5) / (s / (n as f64).sqrt());
+    let p_value = 2.0 * (1.0 - cdfStudent((n - 1) as f64, statistic.abs()));
+    TestOutcome {
+        name: "mean",
+        statistic,
+        p_value,
+        passed: p_value >= alpha,
+    }
+}
+
+/// Battery of all the tests with `samples` draws each at significance `alpha`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StreamTestSuite {
+    pub samples: usize,
+    pub alpha: f64,
+}
+
+impl StreamTestSuite {
+    pub fn new(samples: usize, alpha: f64) -> Self {
+        debug_assert!(samples >= 2560, "Use at least 2560 samples");
+        debug_assert!(0.0 < alpha && alpha < 1.0, "Use 0.0 < alpha < 1.0");
+        StreamTestSuite { samples, alpha }
+    }
+
+    /// Runs every test on the stream `source` currently draws from.
+    pub fn run(&self, source: &mut dyn UniformSource) -> Vec<TestOutcome> {
+        let (n, alpha) = (self.samples, self.alpha);
+        vec![
+            uniformity(source, n, 256, alpha),
+            serial(source, n, 16, alpha),
+            runs_up(source, n / 2, 6, alpha),
+            gap(source, n / 2, 0.94, 1.0, 49, alpha),
+            kolmogorov_smirnov(source, n, alpha),
+            mean(source, n, alpha),
+        ]
+    }
+
+    /// Runs every test on each of `streams`, restoring the stream selected before.
+    pub fn run_streams(
+        &self,
+        source: &mut dyn UniformSource,
+        streams: impl IntoIterator<Item = usize>,
+    ) -> Vec<StreamReport> {
+        streams
+            .into_iter()
+            .map(|stream| StreamReport {
+                stream,
+                outcomes: self.run(&mut source.stream(stream)),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Random::{rngs::RandomGenerator, Mrg32k3a::Mrg32k3a, Pcg64::Pcg64, Xoshiro256::Xoshiro256};
+
+    use super::*;
+
+    /// Lehmer generator with modulus 2^16 + 1 and a poor multiplier.
+    struct Toy(u64);
+
+    impl UniformSource for Toy {
+        fn Random(&mut self) -> f64 {
+            self.0 = self.0 * 75 % 65537;
+            self.0 as f64 / 65537.0
+        }
+        fn restart(&mut self, _stream: usize, seed: u64) {
+            self.0 = seed % 65536 + 1;
+        }
+    }
+
+    #[test]
+    fn test_generators_pass() {
+        let suite = StreamTestSuite::new(100000, 0.01);
+        let mut sources: Vec<Box<dyn UniformSource>> = vec![
+            Box::new(RandomGenerator::with_seed(12345)),
+            Box::new(Mrg32k3a::new()),
+            Box::new(Pcg64::new(7)),
+            Box::new(Xoshiro256::new(7)),
+        ];
+        // 72 tests at alpha = 0.01: a couple of failures are expected by chance
+        let mut failures = Vec::new();
+        for source in sources.iter_mut() {
+            for report in suite.run_streams(source.as_mut(), [0, 1, 5]) {
+                failures.extend(report.failures());
+            }
+        }
+        assert!(failures.len() <= 3, "failed {:?}", failures);
+    }
+
+    #[test]
+    fn test_poor_generators_fail() {
+        let suite = StreamTestSuite::new(100000, 0.01);
+        let outcomes = suite.run(&mut Toy(1));
+        assert!(outcomes.iter().any(|o| !o.passed));
+
+        let mut counter = 0.0;
+        struct Counter<'a>(&'a mut f64);
+        impl UniformSource for Counter<'_> {
+            fn Random(&mut self) -> f64 {
+                *self.0 = (*self.0 + 0.618034) % 1.0;
+                *self.0
+            }
+            fn restart(&mut self, _stream: usize, _seed: u64) {
+                *self.0 = 0.0;
+            }
+        }
+        let outcomes = suite.run(&mut Counter(&mut counter));
+        assert!(!outcomes.iter().find(|o| o.name == "serial").unwrap().passed);
+        assert!(!outcomes.iter().find(|o| o.name == "uniformity").unwrap().passed);
+    }
+}
@@ -0,0 +1,661 @@
+/* --------------------------------------------------------------------------
+ * Common random numbers: every source of randomness of a station (its
+ * arrivals, its services, its routing decisions) draws from a stream of
+ * its own, so two variants of a model built with the same StreamPlan
+ * consume identical sequences wherever they share a station and purpose.
+ *
+ * Antithetic variates: a replication paired with another one draws 1 - U
+ * wherever the other drew U. Variates should be generated by inversion
+ * (see SamplingMode::Inversion) for the pair to be negatively correlated.
+ *
+ * Control variates: Y is corrected by an observation X of known mean mu,
+ *
+ *      Y(beta) = Y - beta (X - mu),    beta = Cov(Y, X) / Var(X)
+ *
+ * which reduces the variance by the factor 1 - rho^2.
+ *
+ * Importance sampling: variates are drawn from a biased density g in place
+ * of the nominal f, every observation weighted by the likelihood ratio
+ * f(x) / g(x) (multiplied along the variates a job draws, see
+ * Event::likelihood).
+ * --------------------------------------------------------------------------
+ */
+
+use std::collections::HashMap;
+
+use crate::{
+    Collections::Matrix::Matrix,
+    Sources::{
+        ArrivalProcess::{PoissonArrivals, RenewalArrivals},
+        Map::MarkovianArrivals,
+        NonHomogeneous::NonHomogeneousPoisson,
+    },
+    Stations::{DepartureHandler::RoutedDepartureHandler, Routing::RoutingMatrix},
+};
+
+use super::{
+    rngs::{with_global_source, UniformSource},
+    rvgs::Variates,
+    rvms::idfStudent,
+    Distributions::Distribution,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Purpose {
+    Arrivals,
+    Services,
+    Routing,
+}
+
+/// Streams dedicated to (station, purpose) pairs, `first` plus the offset registered
+/// for the pair. Variants of a model registering the same offsets draw the same
+/// numbers wherever they share a pair, whatever order they are built in.
+#[derive(Clone, Debug, Default)]
+pub struct StreamPlan {
+    first: usize,
+    offsets: HashMap<(String, Purpose), usize>,
+    antithetic: bool,
+}
+
+impl StreamPlan {
+    pub fn new(first: usize) -> Self {
+        StreamPlan {
+            first,
+            offsets: HashMap::new(),
+            antithetic: false,
+        }
+    }
+
+    /// Samplers, arrivals and routers built by the plan draw 1 - U instead of U.
+    pub fn antithetic(mut self, antithetic: bool) -> Self {
+        self.antithetic = antithetic;
+        self
+    }
+
+    pub fn is_antithetic(&self) -> bool {
+        self.antithetic
+    }
+
+    /// Dedicates stream `first + offset` to `purpose` at `station`, panics if another
+    /// pair already has the offset.
+    pub fn register(&mut self, station: &str, purpose: Purpose, offset: usize) -> &mut Self {
+        let key = (station.to_string(), purpose);
+        if let Some(((other, other_purpose), _)) = self.offsets.iter().find(|(k, o)| **o == offset && **k != key) {
+            panic!("Offset {} already dedicated to {:?} at {}", offset, other_purpose, other);
+        }
+        self.offsets.insert(key, offset);
+        self
+    }
+
+    /// Stream dedicated to `purpose` at `station`, panics if the pair was not registered.
+    pub fn stream(&self, station: &str, purpose: Purpose) -> usize {
+        match self.offsets.get(&(station.to_string(), purpose)) {
+            Some(offset) => self.first + offset,
+            None => panic!("No stream registered for {:?} at {}", purpose, station),
+        }
+    }
+
+    /// Streams registered.
+    pub fn assigned(&self) -> impl Iterator<Item = (&str, Purpose, usize)> {
+        self.offsets
+            .iter()
+            .map(|((station, purpose), offset)| (station.as_str(), *purpose, self.first + offset))
+    }
+
+    /// Number of streams from `first` on the plan may draw from, the highest offset plus one.
+    pub fn span(&self) -> usize {
+        self.offsets.values().max().map_or(0, |offset| offset + 1)
+    }
+
+    /// Closure sampling `distribution` from the stream of `purpose` at `station`
+    /// of the global source.
+    pub fn sampler(
+        &self,
+        distribution: impl Distribution + 'static,
+        station: &str,
+        purpose: Purpose,
+    ) -> impl FnMut() -> f64 {
+        let stream = self.stream(station, purpose);
+        let antithetic = self.antithetic;
+        move || {
+            with_global_source(|global| {
+                let mut source = global.stream(stream);
+                if antithetic {
+                    distribution.sample(&mut Antithetic::new(source))
+                } else {
+                    distribution.sample(&mut source)
+                }
+            })
+        }
+    }
+
+    /// Renewal arrivals to `station` drawing from its arrivals stream.
+    pub fn arrivals(&self, interarrival: impl Distribution + 'static, station: &str) -> RenewalArrivals {
+        RenewalArrivals::new(interarrival, self.stream(station, Purpose::Arrivals)).antithetic(self.antithetic)
+    }
+
+    /// Poisson arrivals to `station` drawing from its arrivals stream.
+    pub fn poisson(&self, rate: f64, station: &str) -> PoissonArrivals {
+        PoissonArrivals::new(rate, self.stream(station, Purpose::Arrivals)).antithetic(self.antithetic)
+    }
+
+    /// Arrivals to `station` at rate `rate`, bounded by `maxRate`, thinned on its arrivals stream.
+    pub fn non_homogeneous(
+        &self,
+        rate: impl Fn(f64) -> f64 + 'static,
+        maxRate: f64,
+        station: &str,
+    ) -> NonHomogeneousPoisson {
+        NonHomogeneousPoisson::from_function(rate, maxRate, self.stream(station, Purpose::Arrivals))
+            .antithetic(self.antithetic)
+    }
+
+    /// Piecewise constant arrival rate to `station`, see `NonHomogeneousPoisson::piecewise`.
+    pub fn piecewise(&self, times: Vec<f64>, rates: Vec<f64>, station: &str) -> NonHomogeneousPoisson {
+        NonHomogeneousPoisson::piecewise(times, rates, self.stream(station, Purpose::Arrivals))
+            .antithetic(self.antithetic)
+    }
+
+    /// Markovian arrivals to `station` drawing from its arrivals stream.
+    pub fn markovian(&self, d0: Matrix<f64>, d1: Matrix<f64>, station: &str) -> MarkovianArrivals {
+        MarkovianArrivals::new(d0, d1, self.stream(station, Purpose::Arrivals)).antithetic(self.antithetic)
+    }
+
+    /// Markov-modulated Poisson arrivals to `station`, see `MarkovianArrivals::mmpp`.
+    pub fn mmpp(&self, q: Matrix<f64>, rates: Vec<f64>, station: &str) -> MarkovianArrivals {
+        MarkovianArrivals::mmpp(q, rates, self.stream(station, Purpose::Arrivals)).antithetic(self.antithetic)
+    }
+
+    /// Departure handler routing the jobs leaving `station` from its routing stream.
+    pub fn router(&self, routing: &RoutingMatrix, station: &str) -> RoutedDepartureHandler {
+        routing
+            .handler(station, self.stream(station, Purpose::Routing))
+            .antithetic(self.antithetic)
+    }
+}
+
+/// Source returning 1 - U for every U of the wrapped one.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Antithetic<S: UniformSource> {
+    source: S,
+}
+
+impl<S: UniformSource> Antithetic<S> {
+    pub fn new(source: S) -> Self {
+        Antithetic { source }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+impl<S: UniformSource> UniformSource for Antithetic<S> {
+    fn Random(&mut self) -> f64 {
+        1.0 - self.source.Random()
+    }
+    fn streams(&self) -> usize {
+        self.source.streams()
+    }
+    fn SelectStream(&mut self, index: usize) {
+        self.source.SelectStream(index)
+    }
+    fn selected(&self) -> usize {
+        self.source.selected()
+    }
+    fn restart(&mut self, stream: usize, seed: u64) {
+        self.source.restart(stream, seed)
+    }
+}
+
+/// Draws from `biased` in place of `nominal`, returning each value with its likelihood ratio.
+pub struct ImportanceSampler {
+    nominal: Box<dyn Distribution>,
+    biased: Box<dyn Distribution>,
+}
+
+impl ImportanceSampler {
+    pub fn new(nominal: impl Distribution + 'static, biased: impl Distribution + 'static) -> Self {
+        ImportanceSampler {
+            nominal: Box::new(nominal),
+            biased: Box::new(biased),
+        }
+    }
+
+    /// f(x) / g(x), zero outside the support of the nominal distribution. The biased
+    /// distribution must be positive wherever the nominal one is.
+    pub fn ratio(&self, x: f64) -> f64 {
+        let nominal = self.nominal.pdf(x);
+        if nominal == 0.0 {
+            return 0.0;
+        }
+        let biased = self.biased.pdf(x);
+        debug_assert!(biased > 0.0, "Nominal density {} at {} where the biased one is {}", nominal, x, biased);
+        nominal / biased
+    }
+
+    pub fn sample(&self, rng: &mut dyn Variates) -> (f64, f64) {
+        let x = self.biased.sample(rng);
+        (x, self.ratio(x))
+    }
+}
+
+/// Control-variate estimator of the mean of Y from pairs (Y, X), E[X] known.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ControlVariate {
+    known_mean: f64,
+    n: usize,
+    mean_y: f64,
+    mean_x: f64,
+    ss_y: f64,
+    ss_x: f64,
+    ss_xy: f64,
+}
+
+impl ControlVariate {
+    pub fn new(known_mean: f64) -> Self {
+        ControlVariate {
+            known_mean,
+            ..Default::default()
+        }
+    }
+
+    pub fn add(&mut self, y: f64, x: f64) {
+        self.n += 1;
+        let n = self.n as f64;
+        let (dy, dx) = (y - self.mean_y, x - self.mean_x);
+        self.mean_y += dy / n;
+        self.mean_x += dx / n;
+        self.ss_y += dy * (y - self.mean_y);
+        self.ss_x += dx * (x - self.mean_x);
+        self.ss_xy += dy * (x - self.mean_x);
+    }
+
+    pub fn count(&self) -> usize {
+        self.n
+    }
+
+    /// Mean of Y without the correction.
+    pub fn crude_mean(&self) -> f64 {
+        self.mean_y
+    }
+
+    /// Estimated optimal coefficient Cov(Y, X) / Var(X).
+    pub fn beta(&self) -> f64 {
+        if self.ss_x > 0.0 {
+            self.ss_xy / self.ss_x
+        } else {
+            0.0
+        }
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean_y - self.beta() * (self.mean_x - self.known_mean)
+    }
+
+    /// Squared sample correlation of Y and X.
+    fn rho2(&self) -> f64 {
+        if self.ss_x > 0.0 && self.ss_y > 0.0 {
+            self.ss_xy * self.ss_xy / (self.ss_x * self.ss_y)
+        } else {
+            0.0
+        }
+    }
+
+    /// Variance of the controlled over the crude estimator, 1 - rho^2.
+    pub fn reduction(&self) -> f64 {
+        1.0 - self.rho2()
+    }
+
+    /// Estimated variance of `mean()` from the residuals of the regression of Y on X.
+    pub fn variance(&self) -> f64 {
+        if self.n < 3 {
+            return f64::NAN;
+        }
+        let n = self.n as f64;
+        let residual = self.ss_y * (1.0 - self.rho2()) / (n - 2.0);
+        let offset = self.mean_x - self.known_mean;
+        let leverage = if self.ss_x > 0.0 { offset * offset / self.ss_x } else { 0.0 };
+        residual * (1.0 / n + leverage)
+    }
+
+    /// Half width of the confidence interval of `mean()` at level `confidence`.
+    pub fn half_width(&self, confidence: f64) -> f64 {
+        if self.n < 3 {
+            return f64::NAN;
+        }
+        idfStudent((self.n - 2) as u32, 0.5 * (1.0 + confidence)) * self.variance().sqrt()
+    }
+}
+
+/// Estimator of observations weighted by their likelihood ratios.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WeightedEstimator {
+    n: usize,
+    mean: f64,
+    ss: f64,
+    sum_w: f64,
+    sum_w2: f64,
+    sum_wx: f64,
+}
+
+impl WeightedEstimator {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn add(&mut self, value: f64, weight: f64) {
+        let z = value * weight;
+        self.n += 1;
+        let delta = z - self.mean;
+        self.mean += delta / self.n as f64;
+        self.ss += delta * (z - self.mean);
+        self.sum_w += weight;
+        self.sum_w2 += weight * weight;
+        self.sum_wx += z;
+    }
+
+    pub fn count(&self) -> usize {
+        self.n
+    }
+
+    /// Unbiased estimate sum(w x) / n of the nominal mean.
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Estimated variance of `mean()`.
+    pub fn variance(&self) -> f64 {
+        if self.n < 2 {
+            return f64::NAN;
+        }
+        self.ss / ((self.n - 1) * self.n) as f64
+    }
+
+    /// Half width of the confidence interval of `mean()` at level `confidence`.
+    pub fn half_width(&self, confidence: f64) -> f64 {
+        if self.n < 2 {
+            return f64::NAN;
+        }
+        idfStudent((self.n - 1) as u32, 0.5 * (1.0 + confidence)) * self.variance().sqrt()
+    }
+
+    /// Self-normalized estimate sum(w x) / sum(w), biased but of lower variance
+    /// when the weights are known up to a constant.
+    pub fn normalized_mean(&self) -> f64 {
+        self.sum_wx / self.sum_w
+    }
+
+    /// (sum w)^2 / sum w^2, the number of unweighted observations worth as much.
+    pub fn effective_sample_size(&self) -> f64 {
+        if self.sum_w2 > 0.0 {
+            self.sum_w * self.sum_w / self.sum_w2
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        Engines::Engine,
+        Events::Event,
+        Random::{
+            rngs::{use_global_source, RandomGenerator},
+            Distributions::{Exponential, Uniform},
+        },
+        Sources::{ArrivalProcess::ArrivalProcess, SourceRuler::SourcePolicyManager},
+        Stations::{
+            FCFSRuler::FCFSPolicyManager,
+            Station::{IEventManager, Station},
+            StationData::StationData,
+        },
+        TestSupport::{mm1, mm1_server},
+    };
+
+    use super::*;
+
+    /// Runs the model `build` registers on a fresh engine up to 500.
+    fn run(build: impl FnOnce(&mut Engine)) -> StationData {
+        let mut engine = Engine::new();
+        build(&mut engine);
+        engine.run_until(500.0);
+        engine.station("Server").unwrap().get_data().clone()
+    }
+
+    #[test]
+    fn test_common_random_numbers() {
+        // every variant draws from a generator of its own, seeded alike
+        let seeded = || use_global_source(Box::new(RandomGenerator::with_seed(240)));
+        seeded();
+        let base = run(|engine| mm1(engine, &mut StreamPlan::new(240)));
+        // the same model with its keys registered, its samplers built and its
+        // stations added the other way round
+        seeded();
+        let same = run(|engine| {
+            let mut plan = StreamPlan::new(240);
+            plan.register("Server", Purpose::Services, 1)
+                .register("Server", Purpose::Arrivals, 0);
+            assert_eq!(plan.stream("Server", Purpose::Arrivals), 240);
+            assert_eq!((plan.assigned().count(), plan.span()), (2, 2));
+            let mut server = FCFSPolicyManager::new();
+            server.use_engine(engine as *mut Engine);
+            server.set_class_service(0, plan.sampler(Exponential::new(0.5), "Server", Purpose::Services));
+            let mut station = Station::new("Server");
+            station.set_handler(Box::new(server));
+            engine.register_station(Box::new(station));
+            let mut source = SourcePolicyManager::new("Source", plan.arrivals(Exponential::new(1.0), "Server"), "Server", 0);
+            source.use_engine(engine as *mut Engine);
+            source.start(0.0);
+            let mut station = Station::new("Source");
+            station.set_handler(Box::new(source));
+            engine.register_station(Box::new(station));
+        });
+        seeded();
+        let faster = run(|engine| {
+            let server = mm1_server(engine, &mut StreamPlan::new(240), 0.4);
+            engine.register_station(Box::new(server));
+        });
+        assert_eq!((base.arrivals, base.completions), (same.arrivals, same.completions));
+        assert_eq!((base.busyTime, base.areaN), (same.busyTime, same.areaN));
+        assert_eq!(base.arrivals, faster.arrivals);
+        assert!(faster.busyTime < base.busyTime);
+    }
+
+    #[test]
+    fn test_antithetic_arrival_sources() {
+        let mut plan = StreamPlan::new(0);
+        plan.register("A", Purpose::Arrivals, 0);
+        let paired = plan.clone().antithetic(true);
+        let q = Matrix::from_rows(vec![vec![-1.0, 1.0], vec![1.0, -1.0]]);
+        let sources = |plan: &StreamPlan| -> Vec<Box<dyn ArrivalProcess>> {
+            vec![
+                Box::new(plan.poisson(2.0, "A")),
+                Box::new(plan.non_homogeneous(|t| 1.0 + t.sin(), 2.0, "A")),
+                Box::new(plan.piecewise(vec![0.0, 1.0], vec![1.0, 3.0], "A")),
+                Box::new(plan.mmpp(q.clone(), vec![0.5, 4.0], "A").start_in(0)),
+            ]
+        };
+        for (mut plain, mut antithetic) in sources(&plan).into_iter().zip(sources(&paired)) {
+            use_global_source(Box::new(RandomGenerator::with_seed(42)));
+            let first = plain.next_arrival(0.0).unwrap();
+            use_global_source(Box::new(RandomGenerator::with_seed(42)));
+            // the paired source sees 1 - U on the same stream
+            assert_ne!(first, antithetic.next_arrival(0.0).unwrap());
+        }
+        // exponential interarrival times by inversion: e^-rx = 1 - U
+        let draws = |plan: &StreamPlan| {
+            use_global_source(Box::new(RandomGenerator::with_seed(42)));
+            let mut poisson = plan.poisson(2.0, "A");
+            (0..50).map(|_| poisson.next_arrival(0.0).unwrap()).collect::<Vec<f64>>()
+        };
+        for (x, y) in draws(&plan).iter().zip(draws(&paired)) {
+            assert!(((-2.0 * x).exp() + (-2.0 * y).exp() - 1.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "No stream registered")]
+    fn test_unregistered_stream() {
+        let mut plan = StreamPlan::new(0);
+        plan.register("Server", Purpose::Services, 0);
+        plan.stream("Server", Purpose::Arrivals);
+    }
+
+    #[test]
+    #[should_panic(expected = "already dedicated")]
+    fn test_shared_offset() {
+        StreamPlan::new(0)
+            .register("A", Purpose::Services, 0)
+            .register("B", Purpose::Services, 0);
+    }
+
+    /// Station counting the jobs it receives.
+    struct Counter(Rc<RefCell<usize>>);
+
+    impl IEventManager for Counter {
+        fn process_event(&mut self, _event: &Event, _data: &mut StationData) {
+            *self.0.borrow_mut() += 1;
+        }
+    }
+
+    #[test]
+    fn test_antithetic_sources() {
+        let mut plan = StreamPlan::new(0);
+        plan.register("A", Purpose::Arrivals, 0).register("A", Purpose::Routing, 1);
+        let mut routing = RoutingMatrix::new();
+        routing.set(("A", 0), ("B", 0), 0.5).unwrap();
+        let draw = |plan: &StreamPlan| {
+            use_global_source(Box::new(RandomGenerator::with_seed(17)));
+            let mut arrivals = plan.arrivals(Exponential::new(1.0), "A");
+            let times: Vec<f64> = (0..100).map(|_| arrivals.next_arrival(0.0).unwrap()).collect();
+            let mut engine = Engine::new();
+            let routed = Rc::new(RefCell::new(0));
+            let mut station = Station::new("B");
+            station.set_handler(Box::new(Counter(routed.clone())));
+            engine.register_station(Box::new(station));
+            let mut router = plan.router(&routing, "A");
+            router.use_engine(&mut engine as *mut Engine);
+            let mut data = StationData::new();
+            for _ in 0..100 {
+                router.process_event(&Event::gen_departure(0.0), &mut data);
+            }
+            engine.run_until(1.0);
+            let routed = *routed.borrow();
+            (times, routed)
+        };
+        let (plain, routed) = draw(&plan);
+        let (paired, antithetic) = draw(&plan.clone().antithetic(true));
+        // exponential interarrival times by inversion: e^-x = 1 - U
+        for (x, y) in plain.iter().zip(&paired) {
+            assert!(((-x).exp() + (-y).exp() - 1.0).abs() < 1e-12);
+        }
+        // a job routed on U leaves on 1 - U, and the other way round
+        assert_eq!(routed + antithetic, 100);
+    }
+
+    #[test]
+    fn test_antithetic() {
+        let mut plain = RandomGenerator::with_seed(99);
+        let mut paired = Antithetic::new(RandomGenerator::with_seed(99));
+        for _ in 0..100 {
+            assert!((plain.Random() + paired.Random() - 1.0).abs() < 1e-15);
+        }
+
+        let exponential = Exponential::new(1.0);
+        let (mut plain, mut paired) = (paired.into_inner(), Antithetic::new(plain));
+        let n = 2000;
+        let pairs: Vec<(f64, f64)> = (0..n)
+            .map(|_| (exponential.sample_inverse(&mut plain), exponential.sample_inverse(&mut paired)))
+            .collect();
+        let covariance = pairs.iter().map(|(x, y)| (x - 1.0) * (y - 1.0)).sum::<f64>() / n as f64;
+        assert!(covariance < -0.5, "covariance {}", covariance);
+        assert!(StreamPlan::new(0).antithetic(true).is_antithetic());
+    }
+
+    #[test]
+    fn test_control_variate() {
+        let mut generator = RandomGenerator::with_seed(4242);
+        let mut estimator = ControlVariate::new(0.5);
+        for _ in 0..1000 {
+            let u = generator.Random();
+            estimator.add(u.exp(), u);
+        }
+        let exact = std::f64::consts::E - 1.0;
+        assert!((estimator.beta() - 1.69).abs() < 0.05, "beta {}", estimator.beta());
+        assert!(estimator.reduction() < 0.05);
+        assert!((estimator.mean() - exact).abs() < estimator.half_width(0.99));
+        assert!((estimator.mean() - exact).abs() < (estimator.crude_mean() - exact).abs());
+    }
+
+    #[test]
+    fn test_importance_sampling() {
+        // P(X > 10) = e^-10 for X ~ Exponential(1), drawn from an exponential of mean 10
+        let mut generator = RandomGenerator::with_seed(31);
+        let sampler = ImportanceSampler::new(Exponential::new(1.0), Exponential::new(10.0));
+        let mut estimator = WeightedEstimator::new();
+        for _ in 0..10000 {
+            let (x, ratio) = sampler.sample(&mut generator);
+            estimator.add(if x > 10.0 { 1.0 } else { 0.0 }, ratio);
+        }
+        let exact = (-10.0f64).exp();
+        assert!((estimator.mean() - exact).abs() < estimator.half_width(0.99));
+        assert!((estimator.mean() / exact - 1.0).abs() < 0.1);
+        assert!(estimator.effective_sample_size() < estimator.count() as f64);
+    }
+
+    #[test]
+    fn test_importance_support() {
+        let sampler = ImportanceSampler::new(Uniform::new(0.0, 1.0), Exponential::new(1.0));
+        assert_eq!(sampler.ratio(2.0), 0.0);
+        assert!((sampler.ratio(0.5) - 0.5f64.exp()).abs() < 1e-12);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "where the biased one is 0")]
+    fn test_importance_continuity() {
+        ImportanceSampler::new(Exponential::new(1.0), Uniform::new(0.0, 1.0)).ratio(2.0);
+    }
+
+    struct Likelihoods(Rc<RefCell<WeightedEstimator>>);
+
+    impl IEventManager for Likelihoods {
+        fn process_event(&mut self, event: &Event, _data: &mut StationData) {
+            self.0.borrow_mut().add(1.0, event.likelihood);
+        }
+    }
+
+    #[test]
+    fn test_weighted_jobs() {
+        let mut engine = Engine::new();
+        let arrivals = RenewalArrivals::importance(
+            ImportanceSampler::new(Exponential::new(1.0), Exponential::new(1.25)),
+            250,
+        );
+        let mut source = SourcePolicyManager::new("Source", arrivals, "Server", 0);
+        source.use_engine(&mut engine as *mut Engine);
+        source.start(0.0);
+        let mut server = FCFSPolicyManager::new();
+        server.use_engine(&mut engine as *mut Engine);
+        server.set_class_importance(0, ImportanceSampler::new(Exponential::new(0.5), Exponential::new(0.4)), 251);
+        let estimator = Rc::new(RefCell::new(WeightedEstimator::new()));
+        let mut station = Station::new("Source");
+        station.set_handler(Box::new(source));
+        engine.register_station(Box::new(station));
+        let mut station = Station::new("Server");
+        station.set_handler(Box::new(server));
+        station.set_departure_handler(Box::new(Likelihoods(estimator.clone())));
+        engine.register_station(Box::new(station));
+        while engine.next_time().is_some_and(|t| t < 5000.0) {
+            engine.tick();
+        }
+        // the ratio of every job has expectation one
+        let estimator = estimator.borrow();
+        assert!(estimator.count() > 3000);
+        assert!((estimator.mean() - 1.0).abs() < estimator.half_width(0.999), "mean {}", estimator.mean());
+    }
+}
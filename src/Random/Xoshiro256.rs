@@ -0,0 +1,181 @@
+/* --------------------------------------------------------------------------
+ * xoshiro256** of D. Blackman and S. Vigna, period 2^256 - 1. The state is
+ * filled from a 64 bit seed by splitmix64, and stream i starts i jumps of
+ * 2^128 draws after stream 0.
+ * --------------------------------------------------------------------------
+ */
+
+use super::{rngs::UniformSource, Pcg64::to_open_unit};
+
+const JUMP: [u64; 4] = [
+    0x180e_c6d3_3cfd_0aba,
+    0xd5a6_1266_f0c9_392c,
+    0xa958_2618_e03f_c9aa,
+    0x39ab_dc45_29b1_661c,
+];
+
+fn splitmix64(x: &mut u64) -> u64 {
+    *x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *x;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn step(s: &mut [u64; 4]) -> u64 {
+    let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+    let t = s[1] << 17;
+    s[2] ^= s[0];
+    s[3] ^= s[1];
+    s[1] ^= s[2];
+    s[0] ^= s[3];
+    s[2] ^= t;
+    s[3] = s[3].rotate_left(45);
+    result
+}
+
+fn jump(s: &[u64; 4]) -> [u64; 4] {
+    let mut state = *s;
+    let mut jumped = [0; 4];
+    for word in JUMP {
+        for b in 0..64 {
+            if word & (1u64 << b) != 0 {
+                for (j, x) in jumped.iter_mut().enumerate() {
+                    *x ^= state[j];
+                }
+            }
+            step(&mut state);
+        }
+    }
+    jumped
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Xoshiro256 {
+    states: Vec<[u64; 4]>,
+    stream: usize,
+}
+
+impl Xoshiro256 {
+    pub fn new(seed: u64) -> Self {
+        Xoshiro256::from_state(Xoshiro256::seeded(seed))
+    }
+
+    /// State of stream 0 filled from `seed`.
+    fn seeded(seed: u64) -> [u64; 4] {
+        let mut x = seed;
+        [
+            splitmix64(&mut x),
+            splitmix64(&mut x),
+            splitmix64(&mut x),
+            splitmix64(&mut x),
+        ]
+    }
+
+    /// Stream 0 starts from `state`, which must not be all zero.
+    pub fn from_state(state: [u64; 4]) -> Self {
+        debug_assert!(state.iter().any(|x| *x != 0), "State should not be all zero");
+        Xoshiro256 {
+            states: vec![state],
+            stream: 0,
+        }
+    }
+
+    /// Next 64 random bits of the selected stream.
+    pub fn next_u64(&mut self) -> u64 {
+        step(&mut self.states[self.stream])
+    }
+}
+
+impl UniformSource for Xoshiro256 {
+    fn Random(&mut self) -> f64 {
+        to_open_unit(self.next_u64())
+    }
+
+    /// Streams are created on demand, the bound is only nominal.
+    fn streams(&self) -> usize {
+        usize::MAX
+    }
+
+    fn SelectStream(&mut self, index: usize) {
+        while self.states.len() <= index {
+            let next = jump(&self.states[self.states.len() - 1]);
+            self.states.push(next);
+        }
+        self.stream = index;
+    }
+
+    fn selected(&self) -> usize {
+        self.stream
+    }
+
+    fn restart(&mut self, stream: usize, seed: u64) {
+        let selected = self.stream;
+        self.SelectStream(stream);
+        self.states[stream] = (0..stream).fold(Xoshiro256::seeded(seed), |state, _| jump(&state));
+        self.stream = selected;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Engines::Engine,
+        Random::{rvgs::Variates, Distributions::{sampler_with, Exponential}},
+        Stations::{FCFSRuler::FCFSPolicyManager, Station::Station},
+        Events::{DefaultType, Event},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_reference_output() {
+        let mut generator = Xoshiro256::from_state([1, 2, 3, 4]);
+        assert_eq!(generator.next_u64(), 11520);
+        assert_eq!(generator.next_u64(), 0);
+        assert_eq!(generator.next_u64(), 1509978240);
+
+        let mut generator = Xoshiro256::new(2023);
+        generator.SelectStream(1);
+        let x = generator.Random();
+        generator.SelectStream(0);
+        assert_ne!(x, generator.Random());
+        let mean = (0..100000).map(|_| generator.Exponential(2.0)).sum::<f64>() / 100000.0;
+        assert!((mean - 2.0).abs() < 0.05, "mean {}", mean);
+
+        generator.restart(1, 2023);
+        generator.SelectStream(1);
+        assert_eq!(x, generator.Random());
+    }
+
+    #[test]
+    fn test_station_on_xoshiro() {
+        let mut engine = Engine::new();
+        let mut server = FCFSPolicyManager::new();
+        server.use_engine(&mut engine as *mut Engine);
+        server.set_class_service(0, sampler_with(Exponential::new(0.5), Xoshiro256::new(1)));
+        let mut station = Station::new("Server");
+        station.set_handler(Box::new(server));
+        engine.register_station(Box::new(station));
+        let mut arrivals = Xoshiro256::new(2);
+        let mut clock = 0.0;
+        for _ in 0..2000 {
+            clock += arrivals.Exponential(1.0);
+            engine.enqueue(Event::new(
+                DefaultType::ARRIVAL.into(),
+                clock,
+                clock,
+                0.0,
+                clock,
+                "Server".to_string(),
+            ));
+        }
+        while engine.next_time().is_some() {
+            engine.tick();
+        }
+        let data = engine.stations()[0].get_data();
+        assert_eq!(data.completions, 2000);
+        let utilization = data.busyTime / data.clock;
+        assert!((utilization - 0.5).abs() < 0.05, "utilization {}", utilization);
+    }
+}
@@ -1,4 +1,14 @@
 pub mod rngs;
 pub mod rvgs;
 pub mod rvms;
-pub mod RandomVariable;
\ No newline at end of file
+pub mod RandomVariable;
+pub mod Distributions;
+pub mod PhaseType;
+pub mod Empirical;
+pub mod Mrg32k3a;
+pub mod Pcg64;
+pub mod Xoshiro256;
+pub mod StreamTests;
+pub mod GoodnessOfFit;
+pub mod Fitting;
+pub mod VarianceReduction;
\ No newline at end of file
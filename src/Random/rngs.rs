@@ -8,15 +8,32 @@
  */
 
 use once_cell::unsync::Lazy;
-use std::{alloc::Layout, time};
+use std::{cell::RefCell, time};
 const MODULUS: i32 = 2147483647;
 const MULTIPLIER: i32 = 48271; /* DON'T CHANGE THIS VALUE                  */
 const CHECK: i32 = 399268537; /* DON'T CHANGE THIS VALUE                  */
 const STREAMS: i32 = 256; /* # of streams, DON'T CHANGE THIS VALUE    */
 const A256: i32 = 22925; /* jump multiplier, DON'T CHANGE THIS VALUE */
 const DEFAULT: i32 = 123456789; /* initial seed, use 0 < DEFAULT < MODULUS  */
-const Q: f64 = MODULUS as f64/ MULTIPLIER as f64;
-const R: f64 = MODULUS as f64 % MULTIPLIER as f64;
+
+/* ==== NOTE ====
+ * The Lehmer step uses Schrage's decomposition m = a q + r with r < q, so
+ * that a (x mod q) - r (x div q) never overflows an i32:
+ *      x' = a (x mod q) - r (x div q)        (+ m if not positive)
+ * q and r are integers, computed for the multiplier in use (48271 or A256).
+ */
+const fn step(x: i32, a: i32) -> i32 {
+    let q = MODULUS / a;
+    let r = MODULUS % a;
+    let t = a * (x % q) - r * (x / q);
+    if t > 0 {
+        t
+    } else {
+        t + MODULUS
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct RandomGenerator {
     seed: Box<[i32]>,
     stream: usize,
@@ -24,78 +41,224 @@ pub struct RandomGenerator {
 }
 
 impl RandomGenerator {
+    /// Stream 0 starts from DEFAULT, selecting another stream before seeding plants DEFAULT.
     pub fn new() -> Self {
         RandomGenerator {
-            seed: vec![DEFAULT;DEFAULT as usize].into_boxed_slice(),
+            seed: vec![DEFAULT; STREAMS as usize].into_boxed_slice(),
             stream: 0,
             initialized: 0,
         }
     }
 
+    /// Generator with all the streams planted from `seed`, see `PlantSeeds`.
+    pub fn with_seed(seed: i32) -> Self {
+        let mut generator = RandomGenerator::new();
+        generator.PlantSeeds(seed);
+        generator
+    }
+
+    /// Generator no longer behind the rvgs free functions and the stations, seeding it
+    /// has no effect on them.
+    #[deprecated(note = "use `use_global_source` and `with_global_source`")]
     pub fn Global() -> &'static mut Self {
         static mut instance: Lazy<RandomGenerator> = Lazy::new(|| RandomGenerator::new());
         unsafe { &mut instance }
     }
 
     pub fn Random(&mut self) -> f64 {
-
-        unsafe {
-            //this is not safe in multithread
-            let mut t: i32 =
-                (MULTIPLIER as f64 * (self.seed[self.stream] as f64 % Q) - R * (self.seed[self.stream] as f64 / Q)) as i32;
-            if t > 0 {
-                self.seed[self.stream] = t;
-            } else {
-                self.seed[self.stream] = t + MODULUS;
-            }
-            return self.seed[self.stream] as f64 / MODULUS as f64;
-        }
+        //this is not safe in multithread
+        self.seed[self.stream] = step(self.seed[self.stream], MULTIPLIER);
+        self.seed[self.stream] as f64 / MODULUS as f64
     }
 
+    /// Seeds stream 0 with `n` (see `PutSeed`) and every other stream with the
+    /// state the previous one reaches after 8,367,782 draws.
     pub fn PlantSeeds(&mut self, n: i32) {
-        let mut x = n;
-        unsafe {
-            self.initialized = 1;
-            let mut s = self.stream;
-            self.SelectStream(0);
-            self.PutSeed(x);
-            self.stream = s;
-            for j in (1..STREAMS as usize) {
-                x = (A256 as f64 * (self.seed[j - 1] as f64 % Q) - R * (self.seed[j - 1] as f64 / Q)) as i32;
-                self.seed[j] = if x > 0 { x } else { x + MODULUS }
-            }
+        self.initialized = 1;
+        let s = self.stream;
+        self.stream = 0;
+        self.PutSeed(n);
+        self.stream = s;
+        for j in 1..STREAMS as usize {
+            self.seed[j] = step(self.seed[j - 1], A256);
         }
     }
 
+    /// Seeds the selected stream with `n mod MODULUS`; a non-positive `n` takes the
+    /// seed from the system clock, mapped into 1..MODULUS-1.
     pub fn PutSeed(&mut self, n: i32) {
-        let mut ok: bool = false;
-        let mut x: i32 = {
-            if n > 0 {
-                n % MODULUS
-            } else {
-                time::SystemTime::now()
-                    .duration_since(time::SystemTime::UNIX_EPOCH)
-                    .expect("Error in catching system time")
-                    .as_millis() as i32
-            }
+        let x: i32 = if n > 0 {
+            n % MODULUS
+        } else {
+            let millis = time::SystemTime::now()
+                .duration_since(time::SystemTime::UNIX_EPOCH)
+                .expect("Error in catching system time")
+                .as_millis();
+            (millis % (MODULUS as u128 - 1)) as i32 + 1
         };
-
-        unsafe {
-            self.seed[self.stream] = x;
-        }
+        self.seed[self.stream] = if x == 0 { DEFAULT } else { x };
     }
 
     pub fn GetSeed(&self) -> i32 {
-        unsafe { self.seed[self.stream] }
+        self.seed[self.stream]
     }
 
     pub fn SelectStream(&mut self, index: usize) {
-        unsafe {
-            self.stream = index % STREAMS as usize;
+        self.stream = index % STREAMS as usize;
 
-            if self.initialized == 0 && self.stream != 0 {
-                self.PlantSeeds(DEFAULT);
-            }
+        if self.initialized == 0 && self.stream != 0 {
+            self.PlantSeeds(DEFAULT);
+        }
+    }
+
+    /// Number of independent streams.
+    pub fn streams(&self) -> usize {
+        STREAMS as usize
+    }
+
+    /// Stream currently selected.
+    pub fn selected(&self) -> usize {
+        self.stream
+    }
+
+    /// Current state of `stream`, to be given back to `set_state` to replay it.
+    pub fn get_state(&self, stream: usize) -> i32 {
+        self.seed[stream % STREAMS as usize]
+    }
+
+    /// Restarts `stream` from `state`, planting the other streams first if they were not.
+    pub fn set_state(&mut self, stream: usize, state: i32) {
+        debug_assert!(0 < state && state < MODULUS, "State should be in 1..MODULUS-1");
+        if self.initialized == 0 {
+            self.PlantSeeds(DEFAULT);
+        }
+        self.seed[stream % STREAMS as usize] = state;
+    }
+
+    /// Restarts `stream` from the state `PlantSeeds` gives it for `seed mod MODULUS`,
+    /// `DEFAULT` if that is 0.
+    pub fn restart(&mut self, stream: usize, seed: u64) {
+        let n = (seed % MODULUS as u64) as i32;
+        let mut x = if n == 0 { DEFAULT } else { n };
+        for _ in 0..stream % STREAMS as usize {
+            x = step(x, A256);
+        }
+        self.set_state(stream, x);
+    }
+}
+
+impl Default for RandomGenerator {
+    fn default() -> Self {
+        RandomGenerator::new()
+    }
+}
+
+/// Source of Uniform(0, 1) draws, possibly split into independent streams.
+/// Single stream sources keep the default stream methods.
+pub trait UniformSource {
+    fn Random(&mut self) -> f64;
+
+    /// Number of independent streams.
+    fn streams(&self) -> usize {
+        1
+    }
+
+    fn SelectStream(&mut self, _index: usize) {}
+
+    /// Stream currently selected.
+    fn selected(&self) -> usize {
+        0
+    }
+
+    /// Restarts `stream` where it starts when the source is seeded with `seed`,
+    /// the other streams keep their state.
+    fn restart(&mut self, stream: usize, seed: u64);
+}
+
+impl UniformSource for RandomGenerator {
+    fn Random(&mut self) -> f64 {
+        RandomGenerator::Random(self)
+    }
+    fn streams(&self) -> usize {
+        RandomGenerator::streams(self)
+    }
+    fn SelectStream(&mut self, index: usize) {
+        RandomGenerator::SelectStream(self, index)
+    }
+    fn selected(&self) -> usize {
+        RandomGenerator::selected(self)
+    }
+    fn restart(&mut self, stream: usize, seed: u64) {
+        RandomGenerator::restart(self, stream, seed)
+    }
+}
+
+thread_local! {
+    static SOURCE: RefCell<Box<dyn UniformSource>> = RefCell::new(Box::new(RandomGenerator::new()));
+}
+
+/// Replaces the generator behind `with_global_source`, and so behind the rvgs free
+/// functions and the stations, on the calling thread.
+pub fn use_global_source(source: Box<dyn UniformSource>) {
+    SOURCE.with(|global| *global.borrow_mut() = source);
+}
+
+/// Runs `f` on the source shared by the simulations of the calling thread, a
+/// `RandomGenerator::new()` unless replaced. Every thread owns its own source.
+/// NOTE: `f` must not use the global source again (e.g. through the rvgs free
+/// functions), the nested borrow panics.
+pub fn with_global_source<R>(f: impl FnOnce(&mut dyn UniformSource) -> R) -> R {
+    SOURCE.with(|global| f(global.borrow_mut().as_mut()))
+}
+
+/// Handle drawing from a fixed stream of a generator, whatever stream the
+/// generator has selected in the meantime.
+pub struct Stream<'generator> {
+    generator: &'generator mut dyn UniformSource,
+    index: usize,
+}
+
+impl<'generator> Stream<'generator> {
+    pub fn new(generator: &'generator mut dyn UniformSource, index: usize) -> Self {
+        let index = index % generator.streams();
+        Stream { generator, index }
+    }
+
+    pub fn Random(&mut self) -> f64 {
+        let previous = self.generator.selected();
+        self.generator.SelectStream(self.index);
+        let u = self.generator.Random();
+        self.generator.SelectStream(previous);
+        u
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl UniformSource for Stream<'_> {
+    fn Random(&mut self) -> f64 {
+        Stream::Random(self)
+    }
+
+    /// Restarts the stream of the generator the handle draws from.
+    fn restart(&mut self, _stream: usize, seed: u64) {
+        self.generator.restart(self.index, seed)
+    }
+}
+
+impl<'source> dyn UniformSource + 'source {
+    pub fn stream(&mut self, index: usize) -> Stream<'_> {
+        Stream::new(self, index)
+    }
+}
+
+impl RandomGenerator {
+    pub fn stream(&mut self, index: usize) -> Stream<'_> {
+        Stream {
+            generator: self,
+            index: index % STREAMS as usize,
         }
     }
 }
@@ -104,20 +267,86 @@ impl RandomGenerator {
 mod tests {
     use super::*;
 
+    fn modpow(base: i32, mut exponent: u64) -> i32 {
+        let (mut result, mut b, m) = (1u64, base as u64, MODULUS as u64);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result * b % m;
+            }
+            b = b * b % m;
+            exponent >>= 1;
+        }
+        result as i32
+    }
+
+    #[test]
+    fn test_global_source() {
+        use_global_source(Box::new(RandomGenerator::with_seed(7)));
+        let u = with_global_source(|global| global.Random());
+        assert_eq!(u, RandomGenerator::with_seed(7).Random());
+        // other threads keep a source of their own
+        let other = std::thread::spawn(|| with_global_source(|global| global.Random())).join().unwrap();
+        assert_eq!(other, RandomGenerator::new().Random());
+    }
+
     #[test]
     fn Test_Random() {
         let mut gen = RandomGenerator::new();
         gen.SelectStream(0);
         gen.PutSeed(1);
-        let mut u: f64 = 0.0;
         for _ in 0..10000 {
-            u = gen.Random();
+            gen.Random();
         }
         let mut x = gen.GetSeed();
-        let mut ok = x == CHECK;
+        let ok = x == CHECK;
         gen.SelectStream(1);
         gen.PlantSeeds(1);
         x = gen.GetSeed();
         assert!(ok && (x == A256));
     }
+
+    #[test]
+    fn test_streams() {
+        // A256 jumps 8,367,782 draws ahead
+        assert_eq!(modpow(MULTIPLIER, 8367782), A256);
+        let mut gen = RandomGenerator::with_seed(1);
+        assert_eq!(gen.streams(), 256);
+        for j in [0, 1, 2, 17, 128, 255] {
+            assert_eq!(gen.get_state(j), modpow(A256, j as u64));
+        }
+        // every stream reproduces the reference check value from its own seed
+        for j in [3, 200] {
+            gen.set_state(j, 1);
+            gen.SelectStream(j);
+            for _ in 0..10000 {
+                gen.Random();
+            }
+            assert_eq!(gen.GetSeed(), CHECK);
+        }
+
+        let mut gen = RandomGenerator::with_seed(12345);
+        gen.SelectStream(7);
+        let state = gen.get_state(7);
+        let first: Vec<f64> = (0..5).map(|_| gen.Random()).collect();
+        gen.set_state(7, state);
+        assert_eq!(first, (0..5).map(|_| gen.Random()).collect::<Vec<f64>>());
+        assert_eq!(gen.get_state(6), RandomGenerator::with_seed(12345).get_state(6));
+
+        // a state set before any seeding survives the streams being planted
+        let mut gen = RandomGenerator::new();
+        gen.set_state(9, 42);
+        gen.SelectStream(9);
+        assert_eq!(gen.GetSeed(), 42);
+
+        // restarting a stream of a used generator puts it back where seeding would
+        gen.restart(9, 12345);
+        gen.restart(200, 12345);
+        let planted = RandomGenerator::with_seed(12345);
+        assert_eq!((gen.get_state(9), gen.get_state(200)), (planted.get_state(9), planted.get_state(200)));
+
+        gen.PutSeed(-1);
+        assert!(gen.GetSeed() > 0);
+        gen.PutSeed(0);
+        assert!(gen.GetSeed() > 0);
+    }
 }
@@ -26,6 +26,19 @@
  *                        mean = exp(a + 0.5*b*b)
  *                    variance = (exp(b*b) - 1) * exp(2*a + b*b)
  *
+ * plus eight heavy-tailed and reliability distributions
+ *
+ *      Weibull(a, b)           x > 0         shape a, scale b
+ *      Pareto(a, b)            x > b         shape a, minimum b
+ *      BoundedPareto(a, l, h)  l < x < h     shape a on [l, h]
+ *      Gamma(a, b)             x > 0         a*b          a*b*b
+ *      Beta(a, b)              0 < x < 1     a/(a+b)      a*b/((a+b)^2*(a+b+1))
+ *      Triangular(a, b, c)     a < x < b     (a+b+c)/3    mode c
+ *      Loglogistic(a, b)       x > 0         scale a, shape b
+ *      Cauchy(a, b)            all x         undefined    location a, scale b
+ *
+ * whose moments are given by the corresponding Distribution objects.
+ *
  * Name              : rvgs.c  (Random Variate GeneratorS)
  * Author            : Steve Park & Dave Geyer
  * Implementor       : Matteo Ielacqia
@@ -36,125 +49,403 @@
 
 
 
-use super::rngs::{self, RandomGenerator};
+use super::rngs::{with_global_source, UniformSource};
+
+/** ==========================================================================
+ * Variates drawn from an explicit source of uniforms: available on every
+ * UniformSource (RandomGenerator, Stream, Mrg32k3a, Pcg64, Xoshiro256), so
+ * that independent simulations can own their generators.
+ * The free functions below are thin wrappers over with_global_source.
+ * ==========================================================================
+ */
+pub trait Variates: UniformSource {
+    /** ========================================================
+     * Returns 1 with probability p or 0 with probability 1 - p.
+     * NOTE: use 0.0 < p < 1.0
+     * ========================================================
+     */
+    fn Bernoulli(&mut self, p: f64) -> u32 {
+        if self.Random() < (1.0 - p) {
+            0
+        } else {
+            1
+        }
+    }
+
+    /** ================================================================
+     * Returns a binomial distributed integer between 0 and n inclusive.
+     * NOTE: use n > 0 and 0.0 < p < 1.0
+     * ================================================================
+     */
+    fn Binomial(&mut self, n: u32, p: f64) -> u32 {
+        (0..n).map(|_| self.Bernoulli(p)).sum()
+    }
+
+    /** ===================================================================
+     * Returns an equilikely distributed integer between a and b inclusive.
+     * NOTE: use a < b
+     * ===================================================================
+     */
+    fn Equilikely(&mut self, a: i64, b: i64) -> i64 {
+        a + ((b - a + 1) as f64 * self.Random()) as i64
+    }
+
+    /** ====================================================
+     * Returns a geometric distributed non-negative integer.
+     * NOTE: use 0.0 < p < 1.0
+     * ====================================================
+     */
+    fn Geometric(&mut self, p: f64) -> u32 {
+        ((1.0 - self.Random()).ln() / p.ln()) as u32
+    }
+
+    /** =================================================
+     * Returns a Pascal distributed non-negative integer.
+     * NOTE: use n > 0 and 0.0 < p < 1.0
+     * =================================================
+     */
+    fn Pascal(&mut self, n: u32, p: f64) -> u32 {
+        (0..n).map(|_| self.Geometric(p)).sum()
+    }
+
+    /** =========================================================
+     * Returns an exponentially distributed positive real number.
+     * NOTE: use m > 0.0
+     * =========================================================
+     */
+    fn Exponential(&mut self, m: f64) -> f64 {
+        -m * (1.0 - self.Random()).ln()
+    }
+
+    /** ==================================================
+     * Returns a Poisson distributed non-negative integer.
+     * NOTE: use m > 0
+     * ==================================================
+     */
+    fn Poisson(&mut self, m: f64) -> u32 {
+        let mut t = 0.0;
+        let mut x = 0;
+        while t < m {
+            t += self.Exponential(1.0);
+            x += 1;
+        }
+        x - 1
+    }
+
+    /** ===========================================================
+     * Returns a uniformly distributed real number between a and b.
+     * NOTE: use a < b
+     * ===========================================================
+     */
+    fn Uniform(&mut self, a: f64, b: f64) -> f64 {
+        a + (b - a) * self.Random()
+    }
+
+    /** ==================================================
+     * Returns an Erlang distributed positive real number.
+     * NOTE: use n > 0 and b > 0.0
+     * ==================================================
+     */
+    fn Erlang(&mut self, n: u32, b: f64) -> f64 {
+        (0..n).map(|_| self.Exponential(b)).sum()
+    }
+
+    /** ========================================================================
+     * Returns a normal (Gaussian) distributed real number.
+     * NOTE: use s > 0.0
+     *
+     * Uses a very accurate approximation of the normal idf due to Odeh & Evans,
+     * J. Applied Statistics, 1974, vol 23, pp 96-97.
+     * ========================================================================
+     */
+    fn Normal(&mut self, m: f64, s: f64) -> f64 {
+        const p0: f64 = 0.322232431088;
+        const q0: f64 = 0.099348462606;
+        const p1: f64 = 1.0;
+        const q1: f64 = 0.588581570495;
+        const p2: f64 = 0.342242088547;
+        const q2: f64 = 0.531103462366;
+        const p3: f64 = 0.204231210245e-1;
+        const q3: f64 = 0.103537752850;
+        const p4: f64 = 0.453642210148e-4;
+        const q4: f64 = 0.385607006340e-2;
+
+        let u = self.Random();
+        let t = if u < 0.5 {
+            (-2.0 * u.ln()).sqrt()
+        } else {
+            (-2.0 * (1.0 - u).ln()).sqrt()
+        };
+
+        let p = p0 + t * (p1 + t * (p2 + t * (p3 + t * p4)));
+        let q = q0 + t * (q1 + t * (q2 + t * (q3 + t * q4)));
+
+        let z = if u < 0.5 { (p / q) - t } else { t - (p / q) };
+        m + s * z
+    }
+
+    /** ====================================================
+     * Returns a lognormal distributed positive real number.
+     * NOTE: use b > 0.0
+     * ====================================================
+     */
+    fn Lognormal(&mut self, a: f64, b: f64) -> f64 {
+        (a + b * self.Normal(0.0, 1.0)).exp()
+    }
+
+    /** =====================================================
+     * Returns a chi-square distributed positive real number.
+     * NOTE: use n > 0
+     * =====================================================
+     */
+    fn Chisquare(&mut self, n: u32) -> f64 {
+        (0..n)
+            .map(|_| {
+                let z = self.Normal(0.0, 1.0);
+                z * z
+            })
+            .sum()
+    }
+
+    /** ===========================================
+     * Returns a student-t distributed real number.
+     * NOTE: use n > 0
+     * ===========================================
+     */
+    fn Student(&mut self, n: u32) -> f64 {
+        self.Normal(0.0, 1.0) / (self.Chisquare(n) / n as f64).sqrt()
+    }
+
+    /** ==================================================
+     * Returns a Weibull distributed positive real number.
+     * NOTE: use a > 0.0 (shape) and b > 0.0 (scale)
+     * ==================================================
+     */
+    fn Weibull(&mut self, a: f64, b: f64) -> f64 {
+        b * (-(1.0 - self.Random()).ln()).powf(1.0 / a)
+    }
+
+    /** ===================================================
+     * Returns a Pareto distributed real number above b.
+     * NOTE: use a > 0.0 (shape) and b > 0.0 (minimum)
+     * ===================================================
+     */
+    fn Pareto(&mut self, a: f64, b: f64) -> f64 {
+        b * (1.0 - self.Random()).powf(-1.0 / a)
+    }
+
+    /** ========================================================
+     * Returns a bounded Pareto distributed real between l and h.
+     * NOTE: use a > 0.0 and 0.0 < l < h
+     * ========================================================
+     */
+    fn BoundedPareto(&mut self, a: f64, l: f64, h: f64) -> f64 {
+        let tail = (l / h).powf(a);
+        l * (1.0 - self.Random() * (1.0 - tail)).powf(-1.0 / a)
+    }
+
+    /** ====================================================================
+     * Returns a gamma distributed positive real number, any positive shape.
+     * NOTE: use a > 0.0 (shape) and b > 0.0 (scale)
+     *
+     * Uses the rejection method of Marsaglia & Tsang, ACM TOMS, 2000, vol 26,
+     * pp 363-372, boosting shapes below 1 with a uniform power.
+     * ====================================================================
+     */
+    fn Gamma(&mut self, a: f64, b: f64) -> f64 {
+        if a < 1.0 {
+            let u = self.Random();
+            return self.Gamma(a + 1.0, b) * u.powf(1.0 / a);
+        }
+        let d = a - 1.0 / 3.0;
+        let c = 1.0 / (9.0 * d).sqrt();
+        loop {
+            let z = self.Normal(0.0, 1.0);
+            let v = 1.0 + c * z;
+            if v <= 0.0 {
+                continue;
+            }
+            let v = v * v * v;
+            let u = self.Random();
+            if u.ln() < 0.5 * z * z + d - d * v + d * v.ln() {
+                return d * v * b;
+            }
+        }
+    }
+
+    /** ===========================================
+     * Returns a beta distributed real in (0, 1).
+     * NOTE: use a > 0.0 and b > 0.0
+     * ===========================================
+     */
+    fn Beta(&mut self, a: f64, b: f64) -> f64 {
+        let x = self.Gamma(a, 1.0);
+        let y = self.Gamma(b, 1.0);
+        x / (x + y)
+    }
 
-fn Random()-> f64{
-    RandomGenerator::Global().Random()
+    /** =====================================================
+     * Returns a triangular distributed real between a and b.
+     * NOTE: use a < c < b, c being the mode
+     * =====================================================
+     */
+    fn Triangular(&mut self, a: f64, b: f64, c: f64) -> f64 {
+        let u = self.Random();
+        if u < (c - a) / (b - a) {
+            a + (u * (b - a) * (c - a)).sqrt()
+        } else {
+            b - ((1.0 - u) * (b - a) * (b - c)).sqrt()
+        }
+    }
+
+    /** =====================================================
+     * Returns a log-logistic distributed positive real number.
+     * NOTE: use a > 0.0 (scale) and b > 0.0 (shape)
+     * =====================================================
+     */
+    fn Loglogistic(&mut self, a: f64, b: f64) -> f64 {
+        let u = self.Random();
+        a * (u / (1.0 - u)).powf(1.0 / b)
+    }
+
+    /** ============================================
+     * Returns a Cauchy distributed real number.
+     * NOTE: use b > 0.0 (scale), a is the location
+     * ============================================
+     */
+    fn Cauchy(&mut self, a: f64, b: f64) -> f64 {
+        a + b * (std::f64::consts::PI * (self.Random() - 0.5)).tan()
+    }
 }
 
-/** ========================================================
- * Returns 1 with probability p or 0 with probability 1 - p.
- * NOTE: use 0.0 < p < 1.0                                   
- * ========================================================
- */
+impl<T: UniformSource + ?Sized> Variates for T {}
+
 pub fn Bernoulli(p: f64) -> u32 {
-    let r = Random();
-    if r < (1.0 - p) {
-        0
-    } else {
-        1
-    }
+    with_global_source(|source| source.Bernoulli(p))
 }
 
-/** ================================================================
- * Returns a binomial distributed integer between 0 and n inclusive.
- * NOTE: use n > 0 and 0.0 < p < 1.0
- * ================================================================
- */
 pub fn Binomial(n: u32, p: f64) -> u32 {
-    let mut x: u32 = 0;
-    for i in 0..n {
-        x += Bernoulli(p);
-    }
-    x
+    with_global_source(|source| source.Binomial(n, p))
+}
+
+pub fn Equilikely(a: i64, b: i64) -> i64 {
+    with_global_source(|source| source.Equilikely(a, b))
 }
 
 pub fn Geometric(p: f64) -> u32 {
-    ((1.0 - Random()).ln() / p.ln()) as u32
+    with_global_source(|source| source.Geometric(p))
 }
 
 pub fn Pascal(n: u32, p: f64) -> u32 {
-    let mut x: u32 = 0;
-    for i in 0..n {
-        x += Geometric(p);
-    }
-    x
+    with_global_source(|source| source.Pascal(n, p))
 }
 
 //also called negative exponential
 pub fn Exponential(m: f64) -> f64 {
-    -m * (1.0 - Random()).ln()
+    with_global_source(|source| source.Exponential(m))
 }
 
 pub fn Poisson(m: f64) -> u32 {
-    let mut t = 0.0;
-    let mut x = 0;
-    while t < m {
-        t += Exponential(1.0);
-        x += 1;
-    }
-    x - 1
+    with_global_source(|source| source.Poisson(m))
 }
 
 pub fn Uniform(a: f64, b: f64) -> f64 {
-    a + (b - a) * Random()
+    with_global_source(|source| source.Uniform(a, b))
 }
 
 pub fn Erlang(n: u32, b: f64) -> f64 {
-    let mut x = 0.0;
-    for i in 0..n {
-        x += Exponential(b);
-    }
-    x
+    with_global_source(|source| source.Erlang(n, b))
 }
 
 pub fn Normal(m: f64, s: f64) -> f64 {
-    const p0: f64 = 0.322232431088;
-    const q0: f64 = 0.099348462606;
-    const p1: f64 = 1.0;
-    const q1: f64 = 0.588581570495;
-    const p2: f64 = 0.342242088547;
-    const q2: f64 = 0.531103462366;
-    const p3: f64 = 0.204231210245e-1;
-    const q3: f64 = 0.103537752850;
-    const p4: f64 = 0.453642210148e-4;
-    const q4: f64 = 0.385607006340e-2;
+    with_global_source(|source| source.Normal(m, s))
+}
 
-    let (mut u, mut t, mut p, mut q, mut z) = (0.0, 0.0, 0.0, 0.0, 0.0);
-    u = Random();
-    if u < 0.5 {
-        t = (-2.0 * u.ln()).sqrt();
-    } else {
-        t = (-2.0 * u.ln()).sqrt();
-    }
+pub fn Lognormal(a: f64, b: f64) -> f64 {
+    with_global_source(|source| source.Lognormal(a, b))
+}
 
-    p   = p0 + t * (p1 + t * (p2 + t * (p3 + t * p4)));
-    q   = q0 + t * (q1 + t * (q2 + t * (q3 + t * q4)));
+pub fn Chisquare(n: u32) -> f64 {
+    with_global_source(|source| source.Chisquare(n))
+}
 
-    if u< 0.5{
-        z=(p/q)-t;
-    }
-    else{
-        z=t-(p/q);
-    }
-    m + s * z
+pub fn Student(n: u32) -> f64 {
+    with_global_source(|source| source.Student(n))
 }
 
+pub fn Weibull(a: f64, b: f64) -> f64 {
+    with_global_source(|source| source.Weibull(a, b))
+}
 
-pub fn Lognormal(a:f64, b:f64)->f64{
-    (a+b*Normal(0.0, 1.0)).exp()
+pub fn Pareto(a: f64, b: f64) -> f64 {
+    with_global_source(|source| source.Pareto(a, b))
 }
 
-pub fn Chisquare(n:u32) -> f64{
-    let (mut z, mut x) = (0.0,0.0);
-    for i in 0..n{
-        z= Normal(0.0, 1.0);
-        x += z*z;
-    }
-    x
+pub fn BoundedPareto(a: f64, l: f64, h: f64) -> f64 {
+    with_global_source(|source| source.BoundedPareto(a, l, h))
+}
+
+pub fn Gamma(a: f64, b: f64) -> f64 {
+    with_global_source(|source| source.Gamma(a, b))
+}
+
+pub fn Beta(a: f64, b: f64) -> f64 {
+    with_global_source(|source| source.Beta(a, b))
 }
 
-pub fn Student(n: u32) -> f64{
-    Normal(0.0, 1.0)/ (Chisquare(n)/n as f64).sqrt()
+pub fn Triangular(a: f64, b: f64, c: f64) -> f64 {
+    with_global_source(|source| source.Triangular(a, b, c))
 }
 
+pub fn Loglogistic(a: f64, b: f64) -> f64 {
+    with_global_source(|source| source.Loglogistic(a, b))
+}
+
+pub fn Cauchy(a: f64, b: f64) -> f64 {
+    with_global_source(|source| source.Cauchy(a, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Random::rngs::RandomGenerator;
+
+    use super::*;
+
+    #[test]
+    fn test_owned_generators_are_independent() {
+        let mut generator = RandomGenerator::new();
+        generator.PutSeed(12345);
+        let x: Vec<f64> = (0..100).map(|_| generator.Exponential(2.0)).collect();
+        generator.PutSeed(12345);
+        let _ = Exponential(2.0);
+        let y: Vec<f64> = (0..100).map(|_| generator.Exponential(2.0)).collect();
+        assert_eq!(x, y);
+    }
+
+    /// Source returning the same value at every draw.
+    struct Fixed(f64);
+
+    impl UniformSource for Fixed {
+        fn Random(&mut self) -> f64 {
+            self.0
+        }
+        fn restart(&mut self, _stream: usize, _seed: u64) {}
+    }
+
+    #[test]
+    fn test_normal_tails() {
+        // both halves of the Odeh-Evans approximation, accurate to 1.5e-8
+        assert!((Fixed(0.975).Normal(0.0, 1.0) - 1.959964).abs() < 1e-6);
+        assert!((Fixed(0.025).Normal(0.0, 1.0) + 1.959964).abs() < 1e-6);
+        assert!((Fixed(0.5).Normal(3.0, 2.0) - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_stream_handle() {
+        let mut generator = RandomGenerator::new();
+        generator.PlantSeeds(4242);
+        let mut stream = generator.stream(3);
+        let mean = (0..20000).map(|_| stream.Normal(1.0, 2.0)).sum::<f64>() / 20000.0;
+        assert!((mean - 1.0).abs() < 0.1, "mean {}", mean);
+    }
+}
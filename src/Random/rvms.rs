@@ -35,6 +35,17 @@
  *                        mean = Exp(a + 0.5*b*b)
  *                    variance = (Exp(b*b) - 1)*Exp(2*a + b*b)
  *
+ * and for 8 heavy-tailed and reliability random variables
+ *
+ *      Weibull(a, b)            x > 0      shape a, scale b
+ *      Pareto(a, b)             x > b      shape a, minimum b
+ *      BoundedPareto(a, l, h)   l < x < h  shape a
+ *      Gamma(a, b)              x > 0      a*b          a*b*b
+ *      Beta(a, b)               0 < x < 1  a/(a+b)      a*b/((a+b)^2*(a+b+1))
+ *      Triangular(a, b, c)      a < x < b  (a+b+c)/3    mode c
+ *      Loglogistic(a, b)        x > 0      scale a, shape b
+ *      Cauchy(a, b)             all x      location a, scale b
+ *
  * Name            : rvms.c (Random Variable ModelS)
  * Author          : Steve Park & Dave Geyer
  * Language        : ANSI C
@@ -49,6 +60,8 @@ use std::f64::consts::PI;
 use once_cell::sync::Lazy;
 
 pub const TINY: f64 = 1.0e-9;
+const RELATIVE: f64 = 1.0e-12; /* relative tolerance of the inversions  */
+const ITERATIONS: usize = 500; /* cap on the steps of the inversions    */
 pub const SQRT2PI: Lazy<f64> = Lazy::new(|| (2.0 * PI).sqrt()); /* sqrt(2 * pi) */
 
 pub fn pdfBernoulli(p: f64, x: u32) -> f64
@@ -72,7 +85,7 @@ pub fn idfBernoulli(p: f64, u: f64) -> u32
   * NOTE: use 0.0 < p < 1.0 and 0.0 < u < 1.0 
   * =========================================
   */ {
-    return (1 - !(u < 1.0 - p) as u32);
+    return (if u < 1.0 - p { 0 } else { 1 });
 }
 
 pub fn pdfEquilikely(a: u32, b: u32, x: u32) -> f64
@@ -96,7 +109,7 @@ pub fn idfEquilikely(a: u32, b: u32, u: f64) -> u32
   * NOTE: use a <= b and 0.0 < u < 1.0 
   * ============================================
   */ {
-    return (a as u32 + (u as u32 * (b - a + 1)));
+    return (a + (u * (b - a + 1) as f64) as u32);
 }
 
 pub fn pdfBinomial(n: u32, p: f64, x: u32) -> f64 /* ============================================ 
@@ -133,7 +146,7 @@ pub fn idfBinomial(n: u32, p: f64, u: f64) -> u32
         while (cdfBinomial(n, p, x) <= u) {
             x += 1;
         }
-    } else if (cdfBinomial(n, p, 0) > u) {
+    } else if (cdfBinomial(n, p, 0) <= u) {
         while (cdfBinomial(n, p, x - 1) > u) {
             x -= 1;
         }
@@ -198,7 +211,7 @@ pub fn idfPascal(n: u32, p: f64, u: f64) -> u32
         while (cdfPascal(n, p, x as u32) <= u) {
             x += 1.0;
         }
-    } else if (cdfPascal(n, p, 0) > u) {
+    } else if (cdfPascal(n, p, 0) <= u) {
         while (cdfPascal(n, p, x as u32 - 1) > u) {
             x -= 1.0;
         }
@@ -480,7 +493,7 @@ pub fn pdfStudent(n: u32, x: f64) -> f64
   * ===================================
   */ {
     let (mut s, mut t) = (0.0, 0.0);
-    s = -0.5 * (n + 1) as f64 * (1.0 + ((x * x) / n as f64).ln());
+    s = -0.5 * (n + 1) as f64 * (1.0 + (x * x) / n as f64).ln();
     t = -LogBeta(0.5, n as f64 / 2.0);
     return ((s + t).exp() / (n as f64).sqrt());
 }
@@ -519,6 +532,255 @@ pub fn idfStudent(n: u32, u: f64) -> f64
     return x;
 }
 
+pub fn pdfWeibull(a: f64, b: f64, x: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and x > 0.0
+  * ===========================================
+  */ {
+    let t = x / b;
+    (a / b) * t.powf(a - 1.0) * (-t.powf(a)).exp()
+}
+
+pub fn cdfWeibull(a: f64, b: f64, x: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and x > 0.0
+  * ===========================================
+  */ {
+    1.0 - (-(x / b).powf(a)).exp()
+}
+
+pub fn idfWeibull(a: f64, b: f64, u: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and 0.0 < u < 1.0
+  * ===========================================
+  */ {
+    b * (-(1.0 - u).ln()).powf(1.0 / a)
+}
+
+pub fn pdfPareto(a: f64, b: f64, x: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and x > b
+  * ===========================================
+  */ {
+    a * b.powf(a) / x.powf(a + 1.0)
+}
+
+pub fn cdfPareto(a: f64, b: f64, x: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and x > b
+  * ===========================================
+  */ {
+    1.0 - (b / x).powf(a)
+}
+
+pub fn idfPareto(a: f64, b: f64, u: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and 0.0 < u < 1.0
+  * ===========================================
+  */ {
+    b * (1.0 - u).powf(-1.0 / a)
+}
+
+pub fn pdfBoundedPareto(a: f64, l: f64, h: f64, x: f64) -> f64
+/* ==================================================
+  * NOTE: use a > 0.0, 0.0 < l < h and l < x < h
+  * ==================================================
+  */ {
+    a * l.powf(a) * x.powf(-a - 1.0) / (1.0 - (l / h).powf(a))
+}
+
+pub fn cdfBoundedPareto(a: f64, l: f64, h: f64, x: f64) -> f64
+/* ==================================================
+  * NOTE: use a > 0.0, 0.0 < l < h and l < x < h
+  * ==================================================
+  */ {
+    (1.0 - (l / x).powf(a)) / (1.0 - (l / h).powf(a))
+}
+
+pub fn idfBoundedPareto(a: f64, l: f64, h: f64, u: f64) -> f64
+/* ==================================================
+  * NOTE: use a > 0.0, 0.0 < l < h and 0.0 < u < 1.0
+  * ==================================================
+  */ {
+    l * (1.0 - u * (1.0 - (l / h).powf(a))).powf(-1.0 / a)
+}
+
+pub fn pdfGamma(a: f64, b: f64, x: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and x > 0.0
+  * ===========================================
+  */ {
+    ((a - 1.0) * (x / b).ln() - (x / b) - b.ln() - LogGamma(a)).exp()
+}
+
+pub fn cdfGamma(a: f64, b: f64, x: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and x > 0.0
+  * ===========================================
+  */ {
+    InGamma(a, x / b)
+}
+
+fn invert(cdf: impl Fn(f64) -> f64, pdf: impl Fn(f64) -> f64, u: f64, bounds: (f64, f64), start: f64) -> f64
+/* ==========================================================================
+  * Solves cdf(x) = u on (bounds.0, bounds.1) by Newton-Raphson steps, kept
+  * in a bracket around the root: a step leaving it is replaced by a
+  * bisection, geometric while the bracket spans orders of magnitude so that
+  * quantiles close to 0 are reached in few steps. Stops on a relative
+  * tolerance or after ITERATIONS steps.
+  * ==========================================================================
+  */ {
+    let (mut low, mut high) = bounds;
+    let mut x = start;
+    for _ in 0..ITERATIONS {
+        let f = cdf(x) - u;
+        if f == 0.0 {
+            return x;
+        } else if f < 0.0 {
+            low = x;
+        } else {
+            high = x;
+        }
+        let newton = x - f / pdf(x);
+        let next = if newton.is_finite() && low < newton && newton < high {
+            newton
+        } else if high.is_infinite() {
+            2.0 * low
+        } else if low <= 0.0 {
+            0.1 * high
+        } else if high > 4.0 * low {
+            (low * high).sqrt()
+        } else {
+            0.5 * (low + high)
+        };
+        if (next - x).abs() <= RELATIVE * next.abs() {
+            return next;
+        }
+        x = next;
+    }
+    x
+}
+
+pub fn idfGamma(a: f64, b: f64, u: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and 0.0 < u < 1.0
+  * ===========================================
+  */ {
+    /* start from the mean */
+    invert(|x| cdfGamma(a, b, x), |x| pdfGamma(a, b, x), u, (0.0, f64::INFINITY), a * b)
+}
+
+pub fn pdfBeta(a: f64, b: f64, x: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and 0.0 < x < 1.0
+  * ===========================================
+  */ {
+    ((a - 1.0) * x.ln() + (b - 1.0) * (1.0 - x).ln() - LogBeta(a, b)).exp()
+}
+
+pub fn cdfBeta(a: f64, b: f64, x: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and 0.0 < x < 1.0
+  * ===========================================
+  */ {
+    InBeta(a, b, x)
+}
+
+pub fn idfBeta(a: f64, b: f64, u: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and 0.0 < u < 1.0
+  * ===========================================
+  */ {
+    /* start from the mean */
+    invert(|x| cdfBeta(a, b, x), |x| pdfBeta(a, b, x), u, (0.0, 1.0), a / (a + b))
+}
+
+pub fn pdfTriangular(a: f64, b: f64, c: f64, x: f64) -> f64
+/* ===========================================
+  * NOTE: use a < c < b and a < x < b
+  * ===========================================
+  */ {
+    if x < c {
+        2.0 * (x - a) / ((b - a) * (c - a))
+    } else {
+        2.0 * (b - x) / ((b - a) * (b - c))
+    }
+}
+
+pub fn cdfTriangular(a: f64, b: f64, c: f64, x: f64) -> f64
+/* ===========================================
+  * NOTE: use a < c < b and a < x < b
+  * ===========================================
+  */ {
+    if x < c {
+        (x - a) * (x - a) / ((b - a) * (c - a))
+    } else {
+        1.0 - (b - x) * (b - x) / ((b - a) * (b - c))
+    }
+}
+
+pub fn idfTriangular(a: f64, b: f64, c: f64, u: f64) -> f64
+/* ===========================================
+  * NOTE: use a < c < b and 0.0 < u < 1.0
+  * ===========================================
+  */ {
+    if u < (c - a) / (b - a) {
+        a + (u * (b - a) * (c - a)).sqrt()
+    } else {
+        b - ((1.0 - u) * (b - a) * (b - c)).sqrt()
+    }
+}
+
+pub fn pdfLoglogistic(a: f64, b: f64, x: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and x > 0.0
+  * ===========================================
+  */ {
+    let t = (x / a).powf(b);
+    (b / a) * (x / a).powf(b - 1.0) / ((1.0 + t) * (1.0 + t))
+}
+
+pub fn cdfLoglogistic(a: f64, b: f64, x: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and x > 0.0
+  * ===========================================
+  */ {
+    1.0 / (1.0 + (x / a).powf(-b))
+}
+
+pub fn idfLoglogistic(a: f64, b: f64, u: f64) -> f64
+/* ===========================================
+  * NOTE: use a > 0.0, b > 0.0 and 0.0 < u < 1.0
+  * ===========================================
+  */ {
+    a * (u / (1.0 - u)).powf(1.0 / b)
+}
+
+pub fn pdfCauchy(a: f64, b: f64, x: f64) -> f64
+/* ===========================================
+  * NOTE: use b > 0.0, a and x can be any value
+  * ===========================================
+  */ {
+    let t = (x - a) / b;
+    1.0 / (PI * b * (1.0 + t * t))
+}
+
+pub fn cdfCauchy(a: f64, b: f64, x: f64) -> f64
+/* ===========================================
+  * NOTE: use b > 0.0, a and x can be any value
+  * ===========================================
+  */ {
+    0.5 + ((x - a) / b).atan() / PI
+}
+
+pub fn idfCauchy(a: f64, b: f64, u: f64) -> f64
+/* ===========================================
+  * NOTE: use b > 0.0 and 0.0 < u < 1.0
+  * ===========================================
+  */ {
+    a + b * (PI * (u - 0.5)).tan()
+}
+
 /* ===================================================================
  * The six functions that follow are a 'special function' mini-library
  * used to support the evaluation of pdf, cdf and idf functions.
@@ -705,6 +967,7 @@ pub fn InBeta(a: f64, b: f64, x: f64) -> f64
         /* do nothing */
         swap = 0;
     }
+    let (a, b, x) = (a1, b1, x1);
     if (x > 0.0) {
         factor = (a * (x).ln() + b * (1.0 - x).ln() - LogBeta(a, b)).exp() / a;
     } else {
@@ -772,4 +1035,74 @@ mod tests {
         assert!(idfBinomial(23, 0.11, 0.90)- 5 == 0);
         assert!(idfBinomial(6, 0.5, 0.5)- 3 == 0);
     }
+
+    #[test]
+    fn test_discrete_inverses() {
+        // idf(u) is the smallest x with cdf(x) > u, u exactly on a cdf value included
+        for (n, p) in [(6, 0.5), (23, 0.11)] {
+            let mut previous = 0.0;
+            for x in 0..n {
+                let boundary = cdfBinomial(n, p, x);
+                if boundary > 1.0 - 1e-9 {
+                    break;
+                }
+                assert_eq!(idfBinomial(n, p, boundary), x + 1, "binomial({}, {}) at F({})", n, p, x);
+                assert_eq!(idfBinomial(n, p, 0.5 * (previous + boundary)), x);
+                previous = boundary;
+            }
+            let mut previous = 0.0;
+            for x in 0..40 {
+                let boundary = cdfPascal(n, p, x);
+                if boundary > 1.0 - 1e-9 {
+                    break;
+                }
+                assert_eq!(idfPascal(n, p, boundary), x + 1, "pascal({}, {}) at F({})", n, p, x);
+                assert_eq!(idfPascal(n, p, 0.5 * (previous + boundary)), x);
+                previous = boundary;
+            }
+        }
+        assert_eq!(idfEquilikely(1, 6, 0.99), 6);
+        assert_eq!(idfEquilikely(1, 6, 0.01), 1);
+        assert_eq!(idfBernoulli(0.3, 0.5), 0);
+    }
+
+    #[test]
+    fn test_student_and_incomplete_beta() {
+        // one degree of freedom is the standard Cauchy
+        assert!((pdfStudent(1, 0.0) - std::f64::consts::FRAC_1_PI).abs() < 1e-10);
+        assert!((pdfStudent(1, 1.0) - 0.5 * std::f64::consts::FRAC_1_PI).abs() < 1e-10);
+        // I_x(a, b) = 1 - I_1-x(b, a) on both sides of the swap threshold
+        for x in [0.1, 0.5, 0.9] {
+            assert!((InBeta(5.0, 2.0, x) + InBeta(2.0, 5.0, 1.0 - x) - 1.0).abs() < 1e-9, "x {}", x);
+        }
+        assert!((InBeta(2.0, 5.0, 0.1) - 0.114265).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_small_shape_quantiles() {
+        for a in [0.05, 0.1, 0.5, 3.0] {
+            for u in [0.01, 0.05, 0.2, 0.5, 0.95] {
+                let x = idfGamma(a, 2.0, u);
+                assert!((cdfGamma(a, 2.0, x) / u - 1.0).abs() < 1e-8, "gamma {} {} {}", a, u, x);
+            }
+            // upper quantiles of small shapes are closer to 1 than doubles can tell
+            for u in [0.01, 0.05, 0.2] {
+                for b in [0.05, 2.0] {
+                    let x = idfBeta(a, b, u);
+                    assert!((cdfBeta(a, b, x) / u - 1.0).abs() < 1e-8, "beta {} {} {} {}", a, b, u, x);
+                }
+            }
+        }
+        assert!((idfGamma(0.1, 1.0, 0.05) / 5.9e-14 - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_special_functions() {
+        assert!((InBeta(5.0, 2.0, 0.9) - 0.885735).abs() < 1e-6);
+        assert!((cdfBinomial(6, 0.5, 0) - 0.015625).abs() < 1e-9);
+        assert!((cdfStudent(10.0, 2.228139) - 0.975).abs() < 1e-6);
+        assert!((pdfStudent(10, 1.0) - 0.230362).abs() < 1e-6);
+        assert_eq!(idfBernoulli(0.3, 0.77), 1);
+        assert_eq!(idfEquilikely(1, 6, 0.4), 3);
+    }
 }
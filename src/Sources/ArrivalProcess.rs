@@ -0,0 +1,131 @@
+use crate::Random::{
+    rngs::with_global_source,
+    rvgs::Variates,
+    Distributions::Distribution,
+    VarianceReduction::{Antithetic, ImportanceSampler},
+};
+
+pub trait ArrivalProcess {
+    /// Absolute time of the next arrival after `clock`, `None` once the process is exhausted.
+    fn next_arrival(&mut self, clock: f64) -> Option<f64>;
+
+    /// Service demand carried by the last arrival, `None` lets the station sample it.
+    fn service_demand(&mut self) -> Option<f64> {
+        None
+    }
+
+    /// Likelihood ratio of the last arrival under importance sampling.
+    fn likelihood(&mut self) -> f64 {
+        1.0
+    }
+}
+
+/// Homogeneous Poisson arrivals with the given rate, drawn from `stream` of the global generator.
+pub struct PoissonArrivals {
+    rate: f64,
+    stream: usize,
+    antithetic: bool,
+}
+
+impl PoissonArrivals {
+    pub fn new(rate: f64, stream: usize) -> Self {
+        debug_assert!(rate > 0.0, "Arrival rate should be positive");
+        PoissonArrivals {
+            rate,
+            stream,
+            antithetic: false,
+        }
+    }
+
+    /// Draws 1 - U instead of U, see `VarianceReduction::Antithetic`.
+    pub fn antithetic(mut self, antithetic: bool) -> Self {
+        self.antithetic = antithetic;
+        self
+    }
+}
+
+impl ArrivalProcess for PoissonArrivals {
+    fn next_arrival(&mut self, clock: f64) -> Option<f64> {
+        let mean = 1.0 / self.rate;
+        let interarrival = with_global_source(|global| {
+            let mut source = global.stream(self.stream);
+            if self.antithetic {
+                Antithetic::new(source).Exponential(mean)
+            } else {
+                source.Exponential(mean)
+            }
+        });
+        Some(clock + interarrival)
+    }
+}
+
+enum Interarrival {
+    Nominal(Box<dyn Distribution>),
+    Importance(ImportanceSampler),
+}
+
+/// Renewal arrivals with i.i.d. interarrival times drawn from `stream` of the global generator.
+pub struct RenewalArrivals {
+    interarrival: Interarrival,
+    ratio: f64,
+    stream: usize,
+    antithetic: bool,
+}
+
+impl RenewalArrivals {
+    pub fn new(interarrival: impl Distribution + 'static, stream: usize) -> Self {
+        RenewalArrivals {
+            interarrival: Interarrival::Nominal(Box::new(interarrival)),
+            ratio: 1.0,
+            stream,
+            antithetic: false,
+        }
+    }
+
+    /// Interarrival times drawn from the biased distribution of `importance`, every
+    /// arrival carrying the likelihood ratio of its interarrival time.
+    pub fn importance(importance: ImportanceSampler, stream: usize) -> Self {
+        RenewalArrivals {
+            interarrival: Interarrival::Importance(importance),
+            ratio: 1.0,
+            stream,
+            antithetic: false,
+        }
+    }
+
+    /// Draws 1 - U instead of U, see `VarianceReduction::Antithetic`.
+    pub fn antithetic(mut self, antithetic: bool) -> Self {
+        self.antithetic = antithetic;
+        self
+    }
+
+    fn draw(&mut self, source: &mut dyn Variates) -> f64 {
+        match &self.interarrival {
+            Interarrival::Nominal(distribution) => distribution.sample(source),
+            Interarrival::Importance(importance) => {
+                let (x, ratio) = importance.sample(source);
+                self.ratio = ratio;
+                x
+            }
+        }
+    }
+}
+
+impl ArrivalProcess for RenewalArrivals {
+    fn next_arrival(&mut self, clock: f64) -> Option<f64> {
+        let stream = self.stream;
+        let interarrival = with_global_source(|global| {
+            let mut source = global.stream(stream);
+            if self.antithetic {
+                self.draw(&mut Antithetic::new(source))
+            } else {
+                self.draw(&mut source)
+            }
+        });
+        Some(clock + interarrival)
+    }
+
+    fn likelihood(&mut self) -> f64 {
+        self.ratio
+    }
+}
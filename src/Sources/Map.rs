@@ -0,0 +1,223 @@
+use crate::{
+    Collections::Matrix::Matrix,
+    Random::{rngs::with_global_source, rvgs::Variates, VarianceReduction::Antithetic},
+};
+
+use super::ArrivalProcess::ArrivalProcess;
+
+/// Markovian Arrival Process: `d0` holds the hidden phase transitions, `d1` the
+/// transitions that produce an arrival. The phase is kept from one arrival to the next,
+/// so interarrival times are correlated. Randomness comes from `stream` of the global generator.
+pub struct MarkovianArrivals {
+    d0: Matrix<f64>,
+    d1: Matrix<f64>,
+    phase: Option<usize>,
+    stream: usize,
+    antithetic: bool,
+}
+
+impl MarkovianArrivals {
+    pub fn new(d0: Matrix<f64>, d1: Matrix<f64>, stream: usize) -> Self {
+        debug_assert!(d0.is_square() && d0.rows() == d1.rows() && d1.is_square(), "Dimension mismatch");
+        debug_assert!(
+            (&d0 + &d1).row_sums().iter().all(|s| s.abs() < 1e-9),
+            "Rows of D0 + D1 should sum to zero"
+        );
+        MarkovianArrivals {
+            d0,
+            d1,
+            phase: None,
+            stream,
+            antithetic: false,
+        }
+    }
+
+    /// Markov-modulated Poisson process: the environment moves with the generator `q`
+    /// and arrivals occur at `rates[i]` while in phase `i`.
+    pub fn mmpp(q: Matrix<f64>, rates: Vec<f64>, stream: usize) -> Self {
+        debug_assert!(q.rows() == rates.len(), "One rate per phase is required");
+        let d1 = Matrix::diagonal(&rates);
+        let d0 = &q - &d1;
+        MarkovianArrivals::new(d0, d1, stream)
+    }
+
+    /// Draws 1 - U instead of U, see `VarianceReduction::Antithetic`.
+    pub fn antithetic(mut self, antithetic: bool) -> Self {
+        self.antithetic = antithetic;
+        self
+    }
+
+    pub fn phases(&self) -> usize {
+        self.d0.rows()
+    }
+
+    /// Current phase, `None` before the first arrival.
+    pub fn phase(&self) -> Option<usize> {
+        self.phase
+    }
+
+    /// Starts from `phase` instead of a phase drawn from the stationary distribution at arrivals.
+    pub fn start_in(mut self, phase: usize) -> Self {
+        debug_assert!(phase < self.phases(), "Unknown phase");
+        self.phase = Some(phase);
+        self
+    }
+
+    /// Stationary distribution π of the phase process: π (D0 + D1) = 0, π 1 = 1.
+    pub fn stationary(&self) -> Vec<f64> {
+        let n = self.phases();
+        let mut a = &self.d0 + &self.d1;
+        for i in 0..n {
+            a[(i, n - 1)] = 1.0;
+        }
+        let mut b = vec![0.0; n];
+        b[n - 1] = 1.0;
+        a.solve_left(&b).expect("Phase process should be irreducible")
+    }
+
+    /// λ = π D1 1
+    pub fn rate(&self) -> f64 {
+        self.d1.left_mul(&self.stationary()).iter().sum()
+    }
+
+    /// Phase distribution right after an arrival: φ = π D1 / λ.
+    pub fn embedded(&self) -> Vec<f64> {
+        let v = self.d1.left_mul(&self.stationary());
+        let rate: f64 = v.iter().sum();
+        v.iter().map(|x| x / rate).collect()
+    }
+
+    /// Squared coefficient of variation of the stationary interarrival time.
+    pub fn scv(&self) -> f64 {
+        let m = self.sojourn();
+        let phi = self.embedded();
+        let second = 2.0 * m.left_mul(&m.left_mul(&phi)).iter().sum::<f64>();
+        let mean = m.left_mul(&phi).iter().sum::<f64>();
+        second / (mean * mean) - 1.0
+    }
+
+    /// Lag-`k` autocorrelation of the interarrival times:
+    /// (φ M P^k M 1 - E[X]^2) / Var(X) with M = (-D0)^-1 and P = M D1.
+    pub fn autocorrelation(&self, k: u32) -> f64 {
+        let m = self.sojourn();
+        let p = &m * &self.d1;
+        let phi = self.embedded();
+        let mean = m.left_mul(&phi).iter().sum::<f64>();
+        let variance = 2.0 * m.left_mul(&m.left_mul(&phi)).iter().sum::<f64>() - mean * mean;
+        let joint = m.left_mul(&p.pow(k).left_mul(&m.left_mul(&phi))).iter().sum::<f64>();
+        (joint - mean * mean) / variance
+    }
+
+    fn sojourn(&self) -> Matrix<f64> {
+        self.d0.scale(-1.0).inverse().expect("D0 should be invertible")
+    }
+
+    fn pick(weights: impl Iterator<Item = f64>, mut u: f64) -> usize {
+        let mut last = 0;
+        for (i, w) in weights.enumerate() {
+            if w > 0.0 {
+                if u < w {
+                    return i;
+                }
+                u -= w;
+                last = i;
+            }
+        }
+        last
+    }
+}
+
+impl ArrivalProcess for MarkovianArrivals {
+    fn next_arrival(&mut self, clock: f64) -> Option<f64> {
+        let stream = self.stream;
+        with_global_source(|global| {
+            let mut source = global.stream(stream);
+            if self.antithetic {
+                self.next_from(clock, &mut Antithetic::new(source))
+            } else {
+                self.next_from(clock, &mut source)
+            }
+        })
+    }
+}
+
+impl MarkovianArrivals {
+    fn next_from(&mut self, clock: f64, rng: &mut dyn Variates) -> Option<f64> {
+        let n = self.phases();
+        let mut phase = match self.phase {
+            Some(phase) => phase,
+            None => {
+                let phi = self.embedded();
+                let u = rng.Random();
+                MarkovianArrivals::pick(phi.into_iter(), u)
+            }
+        };
+        let mut time = clock;
+        loop {
+            let total = -self.d0[(phase, phase)];
+            time += rng.Exponential(1.0 / total);
+            // hidden transitions come first, then the ones carrying an arrival
+            let weights = (0..n)
+                .map(|j| if j == phase { 0.0 } else { self.d0[(phase, j)] })
+                .chain((0..n).map(|j| self.d1[(phase, j)]));
+            let next = MarkovianArrivals::pick(weights, rng.Random() * total);
+            if next < n {
+                phase = next;
+            } else {
+                self.phase = Some(next - n);
+                return Some(time);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moments(process: &mut MarkovianArrivals, n: usize) -> (f64, f64, f64) {
+        let mut clock = 0.0;
+        let samples: Vec<f64> = (0..n)
+            .map(|_| {
+                let time = process.next_arrival(clock).unwrap();
+                let x = time - clock;
+                clock = time;
+                x
+            })
+            .collect();
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let variance = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1) as f64;
+        let lag1 = samples.windows(2).map(|w| (w[0] - mean) * (w[1] - mean)).sum::<f64>() / (n - 1) as f64;
+        (mean, variance / (mean * mean), lag1 / variance)
+    }
+
+    #[test]
+    fn test_poisson_as_map() {
+        let process = MarkovianArrivals::new(
+            Matrix::from_rows(vec![vec![-2.0]]),
+            Matrix::from_rows(vec![vec![2.0]]),
+            3,
+        );
+        assert!((process.rate() - 2.0).abs() < 1e-12);
+        assert!((process.scv() - 1.0).abs() < 1e-12);
+        assert!(process.autocorrelation(1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_mmpp_statistics() {
+        let q = Matrix::from_rows(vec![vec![-0.1, 0.1], vec![0.2, -0.2]]);
+        let mut process = MarkovianArrivals::mmpp(q, vec![5.0, 0.5], 4);
+        let pi = process.stationary();
+        assert!((pi[0] - 2.0 / 3.0).abs() < 1e-12);
+        assert!((process.rate() - (2.0 / 3.0 * 5.0 + 1.0 / 3.0 * 0.5)).abs() < 1e-12);
+        assert!(process.scv() > 1.0);
+        let rho = process.autocorrelation(1);
+        assert!(rho > 0.0 && process.autocorrelation(5) < rho);
+
+        let (mean, scv, lag1) = moments(&mut process, 100000);
+        assert!((mean * process.rate() - 1.0).abs() < 0.05, "sample mean {}", mean);
+        assert!((scv - process.scv()).abs() < 0.15 * process.scv(), "sample scv {} vs {}", scv, process.scv());
+        assert!((lag1 - rho).abs() < 0.05, "sample lag-1 {} vs {}", lag1, rho);
+        assert!(process.phase().is_some());
+    }
+}
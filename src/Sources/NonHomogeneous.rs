@@ -0,0 +1,173 @@
+use crate::Random::{rngs::with_global_source, rvgs::Variates, VarianceReduction::Antithetic};
+
+use super::ArrivalProcess::ArrivalProcess;
+
+/// Non-homogeneous Poisson process with rate λ(t), generated by thinning a
+/// homogeneous process running at the bounding rate `maxRate`. Candidates and
+/// thinning draws come from `stream` of the global generator.
+pub struct NonHomogeneousPoisson {
+    rate: Box<dyn Fn(f64) -> f64>,
+    maxRate: f64,
+    period: Option<f64>,
+    horizon: f64,
+    stream: usize,
+    antithetic: bool,
+}
+
+impl NonHomogeneousPoisson {
+    /// `rate` must never exceed `maxRate`.
+    pub fn from_function(rate: impl Fn(f64) -> f64 + 'static, maxRate: f64, stream: usize) -> Self {
+        debug_assert!(maxRate >= 0.0, "Bounding rate should not be negative");
+        NonHomogeneousPoisson {
+            rate: Box::new(rate),
+            maxRate,
+            period: None,
+            horizon: f64::INFINITY,
+            stream,
+            antithetic: false,
+        }
+    }
+
+    /// Rate `rates[i]` holds on `[times[i], times[i + 1])`, the last one holds from
+    /// `times[rates.len() - 1]` onward. Before `times[0]` the rate is zero.
+    pub fn piecewise(times: Vec<f64>, rates: Vec<f64>, stream: usize) -> Self {
+        debug_assert!(times.len() == rates.len(), "One rate per breakpoint is required");
+        debug_assert!(times.windows(2).all(|w| w[0] < w[1]), "Breakpoints should be increasing");
+        debug_assert!(rates.iter().all(|r| *r >= 0.0), "Rates should not be negative");
+        let maxRate = rates.iter().cloned().fold(0.0, f64::max);
+        NonHomogeneousPoisson::from_function(
+            move |t| match times.iter().rposition(|start| *start <= t) {
+                Some(i) => rates[i],
+                None => 0.0,
+            },
+            maxRate,
+            stream,
+        )
+    }
+
+    /// Repeats the profile every `period` time units, evaluating λ(t mod period).
+    pub fn periodic(mut self, period: f64) -> Self {
+        debug_assert!(period > 0.0, "Period should be positive");
+        self.period = Some(period);
+        self
+    }
+
+    /// Draws 1 - U instead of U, see `VarianceReduction::Antithetic`.
+    pub fn antithetic(mut self, antithetic: bool) -> Self {
+        self.antithetic = antithetic;
+        self
+    }
+
+    /// No arrivals are generated after `horizon`.
+    pub fn until(mut self, horizon: f64) -> Self {
+        self.horizon = horizon;
+        self
+    }
+
+    pub fn rate(&self, time: f64) -> f64 {
+        match self.period {
+            Some(period) => (self.rate)(time.rem_euclid(period)),
+            None => (self.rate)(time),
+        }
+    }
+
+    fn next_from(&self, clock: f64, rng: &mut dyn Variates) -> Option<f64> {
+        let mut time = clock;
+        loop {
+            time += rng.Exponential(1.0 / self.maxRate);
+            if time > self.horizon {
+                return None;
+            }
+            if rng.Uniform(0.0, self.maxRate) <= self.rate(time) {
+                return Some(time);
+            }
+        }
+    }
+}
+
+impl ArrivalProcess for NonHomogeneousPoisson {
+    fn next_arrival(&mut self, clock: f64) -> Option<f64> {
+        if self.maxRate <= 0.0 {
+            return None;
+        }
+        with_global_source(|global| {
+            let mut source = global.stream(self.stream);
+            if self.antithetic {
+                self.next_from(clock, &mut Antithetic::new(source))
+            } else {
+                self.next_from(clock, &mut source)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Random::{
+            rngs::{use_global_source, RandomGenerator},
+            rvgs::Exponential,
+        },
+        Sources::ArrivalProcess::PoissonArrivals,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_periodic_piecewise_profile() {
+        let mut process = NonHomogeneousPoisson::piecewise(vec![0.0, 10.0], vec![1.0, 5.0], 30)
+            .periodic(20.0)
+            .until(2000.0);
+        assert_eq!(process.rate(35.0), 5.0);
+        assert_eq!(process.rate(45.0), 1.0);
+        let (mut low, mut high) = (0.0f64, 0.0f64);
+        let mut clock = 0.0;
+        while let Some(time) = process.next_arrival(clock) {
+            if time.rem_euclid(20.0) < 10.0 {
+                low += 1.0;
+            } else {
+                high += 1.0;
+            }
+            clock = time;
+        }
+        assert!(clock <= 2000.0);
+        assert!((low - 1000.0).abs() < 150.0, "off-peak arrivals {}", low);
+        assert!((high - 5000.0).abs() < 350.0, "peak arrivals {}", high);
+    }
+
+    #[test]
+    fn test_function_rate() {
+        let mut process = NonHomogeneousPoisson::from_function(|t| if t < 100.0 { 2.0 } else { 0.0 }, 2.0, 31)
+            .until(500.0);
+        let mut count = 0;
+        let mut clock = 0.0;
+        while let Some(time) = process.next_arrival(clock) {
+            assert!(time < 100.0);
+            count += 1;
+            clock = time;
+        }
+        assert!((count as f64 - 200.0).abs() < 50.0, "arrivals {}", count);
+    }
+
+    #[test]
+    fn test_dedicated_stream() {
+        let arrivals = |interleave: bool| {
+            use_global_source(Box::new(RandomGenerator::with_seed(29)));
+            let mut process = NonHomogeneousPoisson::from_function(|t| 1.0 + t.sin(), 2.0, 32);
+            let mut poisson = PoissonArrivals::new(1.0, 33);
+            let mut clock = 0.0;
+            let mut times = Vec::new();
+            for _ in 0..100 {
+                if interleave {
+                    poisson.next_arrival(clock);
+                    Exponential(1.0);
+                }
+                clock = process.next_arrival(clock).unwrap();
+                times.push(clock);
+            }
+            times
+        };
+        // draws on other streams leave the sequence of the process unchanged
+        assert_eq!(arrivals(false), arrivals(true));
+    }
+}
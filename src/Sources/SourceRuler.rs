@@ -0,0 +1,128 @@
+use crate::{
+    Engines::Engine,
+    Events::{DefaultType, Event},
+    Stations::{Station::IEventManager, StationData::StationData},
+};
+
+use super::ArrivalProcess::ArrivalProcess;
+
+/// Handler of a source station: every event it receives is an arrival generated by
+/// its process, which is forwarded to `destination` before the next one is scheduled.
+pub struct SourcePolicyManager {
+    name: String,
+    process: Box<dyn ArrivalProcess>,
+    destination: String,
+    class: usize,
+    engine: Option<*mut Engine>,
+}
+
+impl IEventManager for SourcePolicyManager {
+    fn process_event(&mut self, event: &Event, data: &mut StationData) {
+        data.update(event.occurTime);
+        if event.kind != DefaultType::ARRIVAL {
+            return;
+        }
+        let clock = data.clock;
+        let service = self.process.service_demand().unwrap_or(event.serviceTime);
+        let mut arrival = Event::new(
+            DefaultType::ARRIVAL.into(),
+            clock,
+            clock,
+            service,
+            clock,
+            self.destination.clone(),
+        );
+        arrival.class = self.class;
+        arrival.likelihood = self.process.likelihood();
+        self.enqueue_event(arrival);
+        self.schedule(clock);
+    }
+}
+
+impl SourcePolicyManager {
+    /// `name` must be the name of the station the handler is installed in.
+    pub fn new(
+        name: &str,
+        process: impl ArrivalProcess + 'static,
+        destination: &str,
+        class: usize,
+    ) -> Self {
+        SourcePolicyManager {
+            name: name.to_string(),
+            process: Box::new(process),
+            destination: destination.to_string(),
+            class,
+            engine: None,
+        }
+    }
+
+    pub fn use_engine(&mut self, engine: *mut Engine) {
+        self.engine = Some(engine);
+    }
+
+    /// Schedules the first arrival after `clock`, call it once before installing the handler.
+    pub fn start(&mut self, clock: f64) {
+        self.schedule(clock);
+    }
+
+    fn schedule(&mut self, clock: f64) {
+        if let Some(time) = self.process.next_arrival(clock) {
+            let event = Event::new(
+                DefaultType::ARRIVAL.into(),
+                clock,
+                time,
+                0.0,
+                time,
+                self.name.clone(),
+            );
+            self.enqueue_event(event);
+        }
+    }
+
+    fn enqueue_event(&mut self, event: Event) {
+        if let Some(engine) = self.engine {
+            unsafe {
+                (*engine).enqueue(event);
+            }
+        } else {
+            Engine::instance().enqueue(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Random::rvgs::Exponential,
+        Sources::ArrivalProcess::PoissonArrivals,
+        Stations::{FCFSRuler::FCFSPolicyManager, Station::Station},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_source_feeds_station() {
+        let mut engine = Engine::new();
+        let mut source = SourcePolicyManager::new("Source", PoissonArrivals::new(1.0, 0), "Server", 0);
+        source.use_engine(&mut engine as *mut Engine);
+        source.start(0.0);
+        let mut server = FCFSPolicyManager::new();
+        server.use_engine(&mut engine as *mut Engine);
+        server.set_class_service(0, || Exponential(0.5));
+        let mut station = Station::new("Source");
+        station.set_handler(Box::new(source));
+        engine.register_station(Box::new(station));
+        let mut station = Station::new("Server");
+        station.set_handler(Box::new(server));
+        engine.register_station(Box::new(station));
+
+        while engine.next_time().is_some_and(|t| t < 10000.0) {
+            engine.tick();
+        }
+
+        let data = engine.stations()[1].get_data();
+        let rate = data.arrivals as f64 / data.clock;
+        assert!((rate - 1.0).abs() < 0.05, "arrival rate {}", rate);
+        assert!(data.completions > 0);
+    }
+}
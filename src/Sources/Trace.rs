@@ -0,0 +1,109 @@
+use std::{io, path::Path};
+
+use serde::Deserialize;
+
+use super::ArrivalProcess::ArrivalProcess;
+
+/// One line of a trace: absolute arrival time and, optionally, the service demand it carries.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub struct TraceRecord {
+    pub timestamp: f64,
+    pub service: Option<f64>,
+}
+
+/// Replays recorded arrivals in order. CSV traces need a `timestamp` column and may
+/// have a `service` column; records without a service let the station sample it.
+#[derive(Clone, Debug, Default)]
+pub struct TraceArrivals {
+    records: Vec<TraceRecord>,
+    next: usize,
+}
+
+impl TraceArrivals {
+    pub fn new(records: Vec<TraceRecord>) -> Self {
+        debug_assert!(
+            records.windows(2).all(|w| w[0].timestamp <= w[1].timestamp),
+            "Timestamps should not decrease"
+        );
+        TraceArrivals { records, next: 0 }
+    }
+
+    pub fn from_reader(reader: impl io::Read) -> Result<Self, csv::Error> {
+        let records = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader)
+            .deserialize()
+            .collect::<Result<Vec<TraceRecord>, _>>()?;
+        Ok(TraceArrivals::new(records))
+    }
+
+    pub fn from_csv(path: impl AsRef<Path>) -> Result<Self, csv::Error> {
+        TraceArrivals::from_reader(std::fs::File::open(path)?)
+    }
+
+    pub fn records(&self) -> &[TraceRecord] {
+        &self.records
+    }
+
+    /// Records not replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.records.len() - self.next
+    }
+}
+
+impl ArrivalProcess for TraceArrivals {
+    fn next_arrival(&mut self, clock: f64) -> Option<f64> {
+        let record = self.records.get(self.next)?;
+        self.next += 1;
+        Some(record.timestamp.max(clock))
+    }
+
+    fn service_demand(&mut self) -> Option<f64> {
+        self.next.checked_sub(1).and_then(|i| self.records[i].service)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Engines::Engine,
+        Sources::SourceRuler::SourcePolicyManager,
+        Stations::{FCFSRuler::FCFSPolicyManager, Station::Station},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_trace_replay() {
+        let path = std::env::temp_dir().join("neslib_trace_replay.csv");
+        std::fs::write(&path, "timestamp,service\n1.0,2.0\n2.0,0.5\n10.0,1.0\n").unwrap();
+        let trace = TraceArrivals::from_csv(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(trace.records().len(), 3);
+        assert!(TraceArrivals::from_reader("timestamp,service\nnow,1.0\n".as_bytes()).is_err());
+
+        let mut engine = Engine::new();
+        let mut source = SourcePolicyManager::new("Trace", trace, "Server", 0);
+        source.use_engine(&mut engine as *mut Engine);
+        source.start(0.0);
+        let mut server = FCFSPolicyManager::new();
+        server.use_engine(&mut engine as *mut Engine);
+        let mut station = Station::new("Trace");
+        station.set_handler(Box::new(source));
+        engine.register_station(Box::new(station));
+        let mut station = Station::new("Server");
+        station.set_handler(Box::new(server));
+        engine.register_station(Box::new(station));
+
+        while engine.next_time().is_some() {
+            engine.tick();
+        }
+
+        let data = engine.stations()[1].get_data();
+        assert_eq!(data.arrivals, 3);
+        assert_eq!(data.completions, 3);
+        // the second job waits for the first one, which leaves at 3.0
+        assert_eq!(data.clock, 11.0);
+        assert!((data.busyTime - 3.5).abs() < 1e-12);
+    }
+}
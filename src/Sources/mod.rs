@@ -0,0 +1,5 @@
+pub mod ArrivalProcess;
+pub mod SourceRuler;
+pub mod NonHomogeneous;
+pub mod Map;
+pub mod Trace;
@@ -1,13 +1,53 @@
-use super::Station::IEventManager;
+use crate::{
+    Engines::Engine,
+    Events::{DefaultType, Event},
+    Random::rngs::with_global_source,
+};
 
+use super::{Routing::RoutingMatrix, Station::IEventManager};
 
 
-struct RoutedDepartureHandler{
-    stream: i32
+
+/// Forwards every job leaving a station to the next one according to a routing matrix,
+/// switching its class as it departs.
+pub struct RoutedDepartureHandler {
+    station: String,
+    routing: RoutingMatrix,
+    stream: usize,
+    antithetic: bool,
+    engine: Option<*mut Engine>,
 }
 
 impl RoutedDepartureHandler {
-    
+    pub fn new(station: &str, routing: RoutingMatrix, stream: usize) -> Self {
+        RoutedDepartureHandler {
+            station: station.to_string(),
+            routing,
+            stream,
+            antithetic: false,
+            engine: None,
+        }
+    }
+
+    /// Routes on 1 - U instead of U, see `VarianceReduction::Antithetic`.
+    pub fn antithetic(mut self, antithetic: bool) -> Self {
+        self.antithetic = antithetic;
+        self
+    }
+
+    pub fn use_engine(&mut self, engine: *mut Engine) {
+        self.engine = Some(engine);
+    }
+
+    fn enqueue_event(&mut self, event: Event) {
+        if let Some(engine) = self.engine {
+            unsafe {
+                (*engine).enqueue(event);
+            }
+        } else {
+            Engine::instance().enqueue(event);
+        }
+    }
 }
 
 impl IEventManager for RoutedDepartureHandler {
@@ -16,6 +56,72 @@ impl IEventManager for RoutedDepartureHandler {
         event: &crate::Events::Event,
         data: &mut super::StationData::StationData,
     ) {
-        todo!()
+        let mut u = with_global_source(|global| global.stream(self.stream).Random());
+        if self.antithetic {
+            u = 1.0 - u;
+        }
+        if let Some(route) = self.routing.choose(&self.station, event.class, u) {
+            let clock = data.clock;
+            let mut next = event.clone();
+            next.kind = DefaultType::ARRIVAL.into();
+            next.subType = DefaultType::ARRIVAL.into();
+            next.createTime = clock;
+            next.occurTime = clock;
+            next.arrivalTime = clock;
+            next.destination = route.station.clone();
+            next.class = route.class;
+            self.enqueue_event(next);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Random::rvgs::Exponential,
+        Stations::{FCFSRuler::FCFSPolicyManager, JobClass::JobClass, Station::Station},
+    };
+
+    use super::*;
+
+    fn routed_station(name: &str, engine: &mut Engine, routing: &RoutingMatrix, stream: usize) -> Station {
+        let mut handler = FCFSPolicyManager::new();
+        handler.use_engine(engine as *mut Engine);
+        handler.set_class_service(0, || Exponential(1.0));
+        handler.set_class_service(1, || Exponential(1.0));
+        let mut departures = routing.handler(name, stream);
+        departures.use_engine(engine as *mut Engine);
+        let mut station = Station::new(name);
+        station.set_handler(Box::new(handler));
+        station.set_departure_handler(Box::new(departures));
+        station
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_class_switching() {
+        let mut engine = Engine::new();
+        let mut routing = RoutingMatrix::new();
+        routing.set(("A", 0), ("B", 1), 1.0).unwrap();
+        routing.set(("B", 1), ("A", 0), 0.5).unwrap();
+        routing.set(("B", 1), ("B", 0), 0.5).unwrap();
+        routing.set(("B", 0), ("A", 0), 1.0).unwrap();
+        let a = routed_station("A", &mut engine, &routing, 3);
+        let b = routed_station("B", &mut engine, &routing, 4);
+        engine.register_station(Box::new(a));
+        engine.register_station(Box::new(b));
+        engine.register_class(JobClass::closed("customers", 4, "A"));
+
+        for _ in 0..20000 {
+            engine.tick();
+        }
+
+        let a = engine.stations()[0].get_data();
+        let b = engine.stations()[1].get_data();
+        assert_eq!(a.classes.len(), 1);
+        // a routed job may still be in transit between the two stations
+        assert!((3..=4).contains(&(a.sysClients + b.sysClients)));
+        assert_eq!(b.classes[1].arrivals, a.classes[0].completions);
+        let switched = b.classes[0].arrivals as f64 / b.classes[1].completions as f64;
+        assert!((switched - 0.5).abs() < 0.05, "switched fraction {}", switched);
+    }
+}
@@ -3,14 +3,20 @@ use std::{borrow::Borrow, collections::VecDeque, rc::Rc};
 use crate::{
     Engines::Engine,
     Events::{DefaultType, Event},
+    Random::{
+        rngs::with_global_source,
+        Distributions::{sampler, Distribution},
+        VarianceReduction::ImportanceSampler,
+    },
 };
 
-use super::{Station::IEventManager, StationData::StationData};
+use super::{Station::{IEventManager, WeightedServiceFn}, StationData::StationData};
 
 pub struct FCFSPolicyManager {
     eventQueue: VecDeque<Event>,
     eventUnderProcess: Option<Event>,
     engine: Option<*mut Engine>,
+    services: Vec<Option<WeightedServiceFn>>,
 }
 
 impl IEventManager for FCFSPolicyManager {
@@ -30,6 +36,7 @@ impl FCFSPolicyManager {
             eventQueue: VecDeque::new(),
             eventUnderProcess: None,
             engine: None,
+            services: Vec::new(),
         }
     }
 
@@ -37,16 +44,48 @@ impl FCFSPolicyManager {
         self.engine = Some(engine);
     }
 
+    /// Samples the service time of every job of `class` arriving at this station,
+    /// overriding the `serviceTime` carried by the arrival event.
+    pub fn set_class_service(&mut self, class: usize, mut service: impl FnMut() -> f64 + 'static) {
+        self.set_class_weighted_service(class, move || (service(), 1.0));
+    }
+
+    /// Like `set_class_service`, the sampler also returns the likelihood ratio the job's
+    /// `likelihood` is multiplied by.
+    pub fn set_class_weighted_service(&mut self, class: usize, service: impl FnMut() -> (f64, f64) + 'static) {
+        if class >= self.services.len() {
+            self.services.resize_with(class + 1, || None);
+        }
+        self.services[class] = Some(Box::new(service));
+    }
+
+    /// Samples the service time of `class` from `distribution`, drawing from `stream` of the global generator.
+    pub fn set_class_distribution(&mut self, class: usize, distribution: impl Distribution + 'static, stream: usize) {
+        self.set_class_service(class, sampler(distribution, stream));
+    }
+
+    /// Samples the service time of `class` from the biased distribution of `importance`,
+    /// drawing from `stream` of the global generator and weighting the job accordingly.
+    pub fn set_class_importance(&mut self, class: usize, importance: ImportanceSampler, stream: usize) {
+        self.set_class_weighted_service(class, move || with_global_source(|global| importance.sample(&mut global.stream(stream))));
+    }
+
     pub fn ProcessArrival(&mut self, evt: &Event, data: &mut StationData) {
         let mut event = evt.clone();
         if evt.subType != DefaultType::INPROCESS {
             data.client_arrived(evt.arrivalTime);
+            data.class_arrived(evt.class);
             event.subType = DefaultType::INPROCESS.into();
+            event.arrivalTime = data.clock;
+            if let Some(Some(service)) = self.services.get_mut(evt.class) {
+                let (time, ratio) = service();
+                event.serviceTime = time;
+                event.likelihood *= ratio;
+            }
         }
         if self.eventUnderProcess.is_none() {
             let mut newevt = event;
             let clock = data.clock;
-            newevt.arrivalTime = clock;
             newevt.occurTime = clock + newevt.serviceTime;
             newevt.createTime = clock;
             newevt.kind = DefaultType::DEPARTURE.into();
@@ -73,11 +112,11 @@ impl FCFSPolicyManager {
         || evt.subType != DefaultType::INPROCESS),"Event departure requested not in process");
         self.eventUnderProcess = None;
         data.client_departure();
-        if data.sysClients > 1 {
-            debug_assert!(!self.eventQueue.is_empty(),"Event queue should not be empty while more than 1 client is in the system");
+        data.class_departure(evt);
+        if data.sysClients > 0 {
+            debug_assert!(!self.eventQueue.is_empty(),"Event queue should not be empty while clients are waiting in the system");
             let mut new_evt = self.eventQueue.pop_front().unwrap();
             let clock = data.clock;
-            new_evt.arrivalTime = clock;
             new_evt.createTime = clock;
             new_evt.occurTime = clock + new_evt.serviceTime;
             new_evt.kind = DefaultType::DEPARTURE.into();
@@ -93,7 +132,7 @@ impl FCFSPolicyManager {
 mod tests {
     use crate::{
         Random::{rngs::RandomGenerator, rvgs::Exponential},
-        Stations::Station::Station,
+        Stations::{JobClass::JobClass, Station::Station},
     };
 
     use super::*;
@@ -118,4 +157,76 @@ mod tests {
 
         println!("{:?}",engine.stations()[0].get_data());
     }
+
+    #[test]
+    fn test_single_waiting_job() {
+        let mut engine = Engine::new();
+        let mut handler = FCFSPolicyManager::new();
+        handler.use_engine(&mut engine as *mut Engine);
+        let mut station = Station::new("Mock");
+        station.set_handler(Box::new(handler));
+        engine.register_station(Box::new(station));
+        for _ in 0..2 {
+            engine.enqueue(Event::new(DefaultType::ARRIVAL.into(), 0.0, 0.0, 1.0, 0.0, "Mock".to_string()));
+        }
+        while engine.has_events() {
+            engine.tick();
+        }
+
+        // the job left alone in the queue is served when the first one departs,
+        // and its response time counts from its arrival
+        let data = engine.stations()[0].get_data();
+        assert_eq!((data.completions, data.sysClients), (2, 0));
+        assert_eq!((data.clock, data.busyTime), (2.0, 2.0));
+        assert_eq!(data.classes[0].mean_response_time(), 1.5);
+    }
+
+    #[test]
+    fn test_class_dependent_service() {
+        let mut engine = Engine::new();
+        let fast = engine.register_class(JobClass::open("fast"));
+        let slow = engine.register_class(JobClass::open("slow"));
+        let mut handler = FCFSPolicyManager::new();
+        handler.use_engine(&mut engine as *mut Engine);
+        handler.set_class_service(fast, || Exponential(0.5));
+        handler.set_class_service(slow, || Exponential(2.0));
+        let mut station = Station::new("Mock");
+        station.set_handler(Box::new(handler));
+        engine.register_station(Box::new(station));
+
+        let mut clock = 0.0;
+        for i in 0..4000 {
+            clock += Exponential(2.0);
+            let mut event = Event::new(DefaultType::ARRIVAL.into(), clock, clock, 0.0, clock, "Mock".to_string());
+            event.class = if i % 2 == 0 { fast } else { slow };
+            engine.enqueue(event);
+        }
+        while engine.has_events() {
+            engine.tick();
+        }
+
+        let data = engine.stations()[0].get_data();
+        assert_eq!(data.completions, 4000);
+        assert_eq!(data.sysClients, 0);
+        assert_eq!(data.classes[fast].completions, 2000);
+        assert_eq!(data.classes[slow].completions, 2000);
+        let busy: f64 = data.classes.iter().map(|c| c.busyTime).sum();
+        assert!((busy - data.busyTime).abs() < 1e-6 * data.busyTime);
+        let area: f64 = data.classes.iter().map(|c| c.areaN).sum();
+        assert!((area - data.areaN).abs() < 1e-6 * data.areaN);
+        let fastService = data.classes[fast].busyTime / 2000.0;
+        let slowService = data.classes[slow].busyTime / 2000.0;
+        assert!((fastService - 0.5).abs() < 0.1, "fast mean service {}", fastService);
+        assert!((slowService - 2.0).abs() < 0.4, "slow mean service {}", slowService);
+        assert!(data.classes[slow].mean_response_time() > slowService);
+
+        // every job left: the response times add up to the area under the population
+        assert_eq!(data.responseTimes.count(), 4000);
+        assert!((data.responseTimes.mean() * 4000.0 - data.areaN).abs() < 1e-6 * data.areaN);
+        assert!((data.serviceTimes.mean() * 4000.0 - data.busyTime).abs() < 1e-6 * data.busyTime);
+        let total = data.waitTimes.mean() + data.serviceTimes.mean();
+        assert!((total - data.responseTimes.mean()).abs() < 1e-9);
+        assert!(data.waitTimes.min() == 0.0 && data.serviceTimes.min() > 0.0);
+        assert!(data.responseTimes.max() >= data.serviceTimes.max());
+    }
 }
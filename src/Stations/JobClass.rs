@@ -0,0 +1,63 @@
+use crate::Events::{DefaultType, Event};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClassKind {
+    /// Jobs enter from outside the network and eventually leave it.
+    Open,
+    /// A fixed population of jobs circulating forever, initially placed at `reference`.
+    Closed { population: u32, reference: String },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct JobClass {
+    pub name: String,
+    pub kind: ClassKind,
+}
+
+impl JobClass {
+    pub fn open(name: &str) -> Self {
+        JobClass {
+            name: name.to_string(),
+            kind: ClassKind::Open,
+        }
+    }
+
+    pub fn closed(name: &str, population: u32, reference: &str) -> Self {
+        JobClass {
+            name: name.to_string(),
+            kind: ClassKind::Closed {
+                population,
+                reference: reference.to_string(),
+            },
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        matches!(self.kind, ClassKind::Closed { .. })
+    }
+
+    /// Arrival events that place the closed population at its reference station at `clock`.
+    /// Open classes have no initial population.
+    pub fn initial_jobs(&self, class: usize, clock: f64) -> Vec<Event> {
+        match &self.kind {
+            ClassKind::Open => Vec::new(),
+            ClassKind::Closed {
+                population,
+                reference,
+            } => (0..*population)
+                .map(|_| {
+                    let mut event = Event::new(
+                        DefaultType::ARRIVAL.into(),
+                        clock,
+                        clock,
+                        0.0,
+                        clock,
+                        reference.clone(),
+                    );
+                    event.class = class;
+                    event
+                })
+                .collect(),
+        }
+    }
+}
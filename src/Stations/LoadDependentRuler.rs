@@ -0,0 +1,260 @@
+use std::collections::VecDeque;
+
+use crate::{
+    Engines::Engine,
+    Events::{DefaultType, Event},
+    Random::{
+        rngs::with_global_source,
+        Distributions::{sampler, Distribution},
+        VarianceReduction::ImportanceSampler,
+    },
+};
+
+use super::{Station::{IEventManager, WeightedServiceFn}, StationData::StationData};
+
+/// Service rate of the station as a function of the number of jobs in it.
+pub type RateFn = Box<dyn Fn(i32) -> f64>;
+
+/// FCFS station whose service rate depends on the current queue length.
+/// The `serviceTime` of a job is its work at unit rate: whenever the population
+/// changes the remaining work of the job in service is rescaled to the new rate
+/// and its departure is rescheduled.
+pub struct LoadDependentPolicyManager {
+    eventQueue: VecDeque<Event>,
+    eventUnderProcess: Option<Event>,
+    engine: Option<*mut Engine>,
+    services: Vec<Option<WeightedServiceFn>>,
+    rate: RateFn,
+    remainingWork: f64,
+    lastUpdate: f64,
+}
+
+impl IEventManager for LoadDependentPolicyManager {
+    fn process_event(&mut self, event: &Event, data: &mut StationData) {
+        data.update(event.occurTime);
+        match DefaultType::from(event.kind) {
+            DefaultType::ARRIVAL => self.ProcessArrival(event, data),
+            DefaultType::DEPARTURE => self.ProcessDeparture(event, data),
+            _ => {}
+        }
+    }
+}
+
+impl LoadDependentPolicyManager {
+    pub fn new(rate: impl Fn(i32) -> f64 + 'static) -> Self {
+        LoadDependentPolicyManager {
+            eventQueue: VecDeque::new(),
+            eventUnderProcess: None,
+            engine: None,
+            services: Vec::new(),
+            rate: Box::new(rate),
+            remainingWork: 0.0,
+            lastUpdate: 0.0,
+        }
+    }
+
+    /// Rates given as a table, `rates[n - 1]` being the rate with `n` jobs in the station.
+    /// Populations beyond the table use the last rate.
+    pub fn from_rates(rates: Vec<f64>) -> Self {
+        debug_assert!(!rates.is_empty(), "At least one rate is required");
+        LoadDependentPolicyManager::new(move |n| {
+            let index = (n.max(1) as usize - 1).min(rates.len() - 1);
+            rates[index]
+        })
+    }
+
+    /// A station with `servers` identical servers, each working at `rate`.
+    pub fn multi_server(servers: u32, rate: f64) -> Self {
+        LoadDependentPolicyManager::new(move |n| n.min(servers as i32) as f64 * rate)
+    }
+
+    pub fn use_engine(&mut self, engine: *mut Engine) {
+        self.engine = Some(engine);
+    }
+
+    pub fn set_class_service(&mut self, class: usize, mut service: impl FnMut() -> f64 + 'static) {
+        self.set_class_weighted_service(class, move || (service(), 1.0));
+    }
+
+    /// Like `set_class_service`, the sampler also returns the likelihood ratio the job's
+    /// `likelihood` is multiplied by.
+    pub fn set_class_weighted_service(&mut self, class: usize, service: impl FnMut() -> (f64, f64) + 'static) {
+        if class >= self.services.len() {
+            self.services.resize_with(class + 1, || None);
+        }
+        self.services[class] = Some(Box::new(service));
+    }
+
+    /// Samples the service time of `class` from `distribution`, drawing from `stream` of the global generator.
+    pub fn set_class_distribution(&mut self, class: usize, distribution: impl Distribution + 'static, stream: usize) {
+        self.set_class_service(class, sampler(distribution, stream));
+    }
+
+    /// Samples the work of `class` from the biased distribution of `importance`,
+    /// drawing from `stream` of the global generator and weighting the job accordingly.
+    pub fn set_class_importance(&mut self, class: usize, importance: ImportanceSampler, stream: usize) {
+        self.set_class_weighted_service(class, move || with_global_source(|global| importance.sample(&mut global.stream(stream))));
+    }
+
+    pub fn ProcessArrival(&mut self, evt: &Event, data: &mut StationData) {
+        let mut event = evt.clone();
+        let population = data.sysClients;
+        if evt.subType != DefaultType::INPROCESS {
+            data.client_arrived(evt.arrivalTime);
+            data.class_arrived(evt.class);
+            event.subType = DefaultType::INPROCESS.into();
+            event.arrivalTime = data.clock;
+            if let Some(Some(service)) = self.services.get_mut(evt.class) {
+                let (work, ratio) = service();
+                event.serviceTime = work;
+                event.likelihood *= ratio;
+            }
+        }
+        if self.eventUnderProcess.is_none() {
+            self.start_service(event, data);
+        } else {
+            self.eventQueue.push_back(event);
+            self.reschedule(population, data);
+        }
+    }
+
+    pub fn ProcessDeparture(&mut self, evt: &Event, data: &mut StationData) {
+        debug_assert!(!(self.eventUnderProcess.is_none()
+        || *self.eventUnderProcess.as_ref().unwrap() != *evt
+        || evt.subType != DefaultType::INPROCESS),"Event departure requested not in process");
+        self.eventUnderProcess = None;
+        data.client_departure();
+        data.class_departure(evt);
+        if let Some(next) = self.eventQueue.pop_front() {
+            self.start_service(next, data);
+        }
+    }
+
+    fn start_service(&mut self, mut event: Event, data: &StationData) {
+        let clock = data.clock;
+        self.remainingWork = event.serviceTime;
+        self.lastUpdate = clock;
+        event.createTime = clock;
+        event.occurTime = clock + self.remainingWork / (self.rate)(data.sysClients);
+        event.kind = DefaultType::DEPARTURE.into();
+        self.enqueue_event(&event);
+        self.eventUnderProcess = Some(event);
+    }
+
+    /// Consumes the work done at the rate of the previous population and moves the
+    /// departure of the job in service to match the rate of the current one.
+    fn reschedule(&mut self, previousPopulation: i32, data: &StationData) {
+        let clock = data.clock;
+        self.remainingWork -= (clock - self.lastUpdate) * (self.rate)(previousPopulation);
+        self.remainingWork = self.remainingWork.max(0.0);
+        self.lastUpdate = clock;
+        let old = self.eventUnderProcess.take().unwrap();
+        self.cancel_event(&old);
+        let mut event = old;
+        event.occurTime = clock + self.remainingWork / (self.rate)(data.sysClients);
+        self.enqueue_event(&event);
+        self.eventUnderProcess = Some(event);
+    }
+
+    fn engine(&mut self) -> &mut Engine {
+        if let Some(engine) = self.engine {
+            unsafe { &mut *engine }
+        } else {
+            Engine::instance()
+        }
+    }
+
+    fn enqueue_event(&mut self, event: &Event) {
+        self.engine().enqueue(event.clone());
+    }
+
+    fn cancel_event(&mut self, event: &Event) {
+        let cancelled = self.engine().cancel(event);
+        debug_assert!(cancelled, "Departure of the job in service was not scheduled");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        Random::{
+            rngs::{use_global_source, RandomGenerator},
+            rvgs::Exponential,
+            Distributions,
+        },
+        Stations::Station::Station,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_multi_server_population() {
+        let mut engine = Engine::new();
+        let mut handler = LoadDependentPolicyManager::multi_server(2, 1.0);
+        handler.use_engine(&mut engine as *mut Engine);
+        handler.set_class_service(0, || Exponential(1.0));
+        let mut station = Station::new("LD");
+        station.set_handler(Box::new(handler));
+        engine.register_station(Box::new(station));
+
+        let mut clock = 0.0;
+        for _ in 0..50000 {
+            clock += Exponential(1.0);
+            while engine.next_time().is_some_and(|t| t < clock) {
+                engine.tick();
+            }
+            engine.enqueue(Event::new(DefaultType::ARRIVAL.into(), clock, clock, 0.0, clock, "LD".to_string()));
+        }
+        while engine.has_events() {
+            engine.tick();
+        }
+
+        // M/M/2 with rho = 0.5: L = 2 rho / (1 - rho^2)
+        let data = engine.stations()[0].get_data();
+        let population = data.areaN / data.clock;
+        assert_eq!(data.completions, 50000);
+        assert!((population - 4.0 / 3.0).abs() < 0.15, "mean population {}", population);
+    }
+
+    struct Likelihoods(Rc<RefCell<Vec<f64>>>);
+
+    impl IEventManager for Likelihoods {
+        fn process_event(&mut self, event: &Event, _data: &mut StationData) {
+            self.0.borrow_mut().push(event.likelihood);
+        }
+    }
+
+    #[test]
+    fn test_class_importance() {
+        use_global_source(Box::new(RandomGenerator::with_seed(260)));
+        let mut engine = Engine::new();
+        let mut handler = LoadDependentPolicyManager::multi_server(2, 1.0);
+        handler.use_engine(&mut engine as *mut Engine);
+        let importance = ImportanceSampler::new(Distributions::Exponential::new(1.0), Distributions::Exponential::new(1.5));
+        handler.set_class_importance(0, importance, 260);
+        let likelihoods = Rc::new(RefCell::new(Vec::new()));
+        let mut station = Station::new("LD");
+        station.set_handler(Box::new(handler));
+        station.set_departure_handler(Box::new(Likelihoods(likelihoods.clone())));
+        engine.register_station(Box::new(station));
+        for i in 0..4000 {
+            let clock = i as f64;
+            while engine.next_time().is_some_and(|t| t < clock) {
+                engine.tick();
+            }
+            engine.enqueue(Event::new(DefaultType::ARRIVAL.into(), clock, clock, 0.0, clock, "LD".to_string()));
+        }
+        while engine.has_events() {
+            engine.tick();
+        }
+
+        // every job carries the ratio of its work, whose expectation is one
+        let likelihoods = likelihoods.borrow();
+        let mean = likelihoods.iter().sum::<f64>() / likelihoods.len() as f64;
+        assert_eq!(likelihoods.len(), 4000);
+        assert!(likelihoods.iter().all(|l| *l != 1.0));
+        assert!((mean - 1.0).abs() < 0.05, "mean likelihood {}", mean);
+    }
+}
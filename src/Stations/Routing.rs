@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::Random::RandomVariable::{AliasTable, WeightError, WEIGHT_TOLERANCE};
+
+use super::DepartureHandler::RoutedDepartureHandler;
+
+/// Destination of a job leaving a station: the next station and the class it
+/// continues with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route {
+    pub station: String,
+    pub class: usize,
+    pub probability: f64,
+}
+
+/// Routing probabilities from (station, class) pairs to (station, class) pairs.
+/// Whatever probability is left out of a row is the chance of leaving the network.
+#[derive(Clone, Debug, Default)]
+pub struct RoutingMatrix {
+    routes: HashMap<(String, usize), Vec<Route>>,
+    /// Alias table per row, its last entry being the exit from the network.
+    tables: HashMap<(String, usize), AliasTable>,
+}
+
+impl RoutingMatrix {
+    pub fn new() -> Self {
+        RoutingMatrix {
+            routes: HashMap::new(),
+            tables: HashMap::new(),
+        }
+    }
+
+    /// Sets the probability that a job of class `from.1` leaving `from.0` joins `to.0` as class `to.1`.
+    /// The matrix is left unchanged if the probability is negative or not finite, or if the row
+    /// would sum to more than 1.
+    pub fn set(&mut self, from: (&str, usize), to: (&str, usize), probability: f64) -> Result<(), WeightError> {
+        let key = (from.0.to_string(), from.1);
+        let mut row = self.routes.get(&key).cloned().unwrap_or_default();
+        if let Some(route) = row.iter_mut().find(|r| r.station == to.0 && r.class == to.1) {
+            route.probability = probability;
+        } else {
+            row.push(Route {
+                station: to.0.to_string(),
+                class: to.1,
+                probability,
+            });
+        }
+        let mut weights: Vec<f64> = row.iter().map(|r| r.probability).collect();
+        let sum = weights.iter().sum::<f64>();
+        if sum > 1.0 + WEIGHT_TOLERANCE {
+            return Err(WeightError::NotNormalized(sum));
+        }
+        weights.push((1.0 - sum).max(0.0));
+        let table = AliasTable::new(&weights)?;
+        self.tables.insert(key.clone(), table);
+        self.routes.insert(key, row);
+        Ok(())
+    }
+
+    pub fn routes(&self, station: &str, class: usize) -> &[Route] {
+        self.routes
+            .get(&(station.to_string(), class))
+            .map(|r| r.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Picks the destination for a uniform draw `u`; `None` means the job leaves the network.
+    pub fn choose(&self, station: &str, class: usize, u: f64) -> Option<&Route> {
+        let key = (station.to_string(), class);
+        let table = self.tables.get(&key)?;
+        self.routes[&key].get(table.sample(u))
+    }
+
+    /// Builds the departure handler routing the jobs leaving `station`, drawing from `stream`.
+    pub fn handler(&self, station: &str, stream: usize) -> RoutedDepartureHandler {
+        let mut rows = RoutingMatrix::new();
+        for ((from, class), row) in &self.routes {
+            if from == station {
+                let key = (from.clone(), *class);
+                rows.tables.insert(key.clone(), self.tables[&key].clone());
+                rows.routes.insert(key, row.clone());
+            }
+        }
+        RoutedDepartureHandler::new(station, rows, stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose() {
+        let mut matrix = RoutingMatrix::new();
+        matrix.set(("A", 0), ("B", 0), 0.25).unwrap();
+        matrix.set(("A", 0), ("B", 1), 0.5).unwrap();
+        let draw = |matrix: &RoutingMatrix| {
+            let mut counts = [0; 3];
+            for i in 0..1000 {
+                match matrix.choose("A", 0, (i as f64 + 0.5) / 1000.0) {
+                    Some(route) => counts[route.class] += 1,
+                    None => counts[2] += 1,
+                }
+            }
+            counts
+        };
+        assert_eq!(draw(&matrix), [250, 500, 250]);
+        assert!(matrix.choose("A", 1, 0.1).is_none());
+        matrix.set(("A", 0), ("B", 0), 0.5).unwrap();
+        assert_eq!(matrix.routes("A", 0).len(), 2);
+        assert_eq!(draw(&matrix), [500, 500, 0]);
+
+        assert_eq!(matrix.set(("A", 0), ("C", 0), -0.1), Err(WeightError::Negative(2)));
+        assert_eq!(matrix.set(("A", 0), ("C", 0), f64::NAN), Err(WeightError::Negative(2)));
+        assert!(matches!(matrix.set(("A", 0), ("B", 0), 0.7), Err(WeightError::NotNormalized(_))));
+        // failed updates leave the row untouched
+        assert_eq!(matrix.routes("A", 0).len(), 2);
+        assert_eq!(draw(&matrix), [500, 500, 0]);
+    }
+}
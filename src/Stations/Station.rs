@@ -20,6 +20,12 @@ pub trait IEventManager {
 
 type Handler = Option<Box<dyn IEventManager>>;
 
+/// Service-time sampler invoked once per job.
+pub type ServiceFn = Box<dyn FnMut() -> f64>;
+
+/// Service-time sampler also returning the likelihood ratio of the value drawn.
+pub type WeightedServiceFn = Box<dyn FnMut() -> (f64, f64)>;
+
 pub struct Station {
     name: String,
     data: StationData,
@@ -48,6 +54,14 @@ impl Station {
         }
     }
 
+    pub fn set_departure_handler(&mut self, handler: Box<dyn IEventManager>) {
+        if self.departure_handler.is_none() {
+            self.departure_handler = Some(handler);
+        } else {
+            error!("Cannot set departure handler 2 times")
+        }
+    }
+
     pub fn handle(&mut self, event: &Event) {
         if let Some(handler) = &mut self.event_handler {
             self.data.update(event.occurTime);
@@ -67,6 +81,15 @@ impl Station {
         &self.data
     }
 
+    /// Accumulates the station statistics up to `time`, no later than the next event.
+    pub fn advance(&mut self, time: f64) {
+        self.data.update(time);
+    }
+
+    pub fn reset_statistics(&mut self) {
+        self.data.reset_statistics();
+    }
+
     pub fn name(&self) -> &String {
         &self.name
     }
@@ -1,6 +1,55 @@
-use crate::Events::Event;
+use crate::{
+    Data::{Quantiles::Histogram, Tally::Tally},
+    Events::Event,
+};
 
-#[derive(Clone, Copy,Debug)]
+/// Relative accuracy of the response time quantiles of every station.
+pub const QUANTILE_ACCURACY: f64 = 0.01;
+const QUANTILE_GAMMA: f64 = (1.0 + QUANTILE_ACCURACY) / (1.0 - QUANTILE_ACCURACY);
+
+/// Counters collected for a single job class at a station.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClassData {
+    pub arrivals: i32,
+    pub completions: i32,
+    pub sysClients: i32,
+    pub busyTime: f64,
+    pub areaN: f64,
+    pub responseTime: f64,
+}
+
+impl ClassData {
+    pub const fn new() -> Self {
+        Self {
+            arrivals: 0,
+            completions: 0,
+            sysClients: 0,
+            busyTime: 0.0,
+            areaN: 0.0,
+            responseTime: 0.0,
+        }
+    }
+
+    pub fn mean_population(&self, clock: f64) -> f64 {
+        if clock > 0.0 {
+            self.areaN / clock
+        } else {
+            0.0
+        }
+    }
+
+    pub fn mean_response_time(&self) -> f64 {
+        if self.completions > 0 {
+            self.responseTime / self.completions as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Accumulators (counts, busy time, areas) cover the period since `resetTime`, zero
+/// unless the statistics were reset at the end of a warm-up period.
+#[derive(Clone, Debug)]
 pub struct StationData {
     pub arrivals: i32,
     pub completions: i32,
@@ -13,6 +62,15 @@ pub struct StationData {
     pub areaS: f64,
     pub oldclock: f64,
     pub clock: f64,
+    pub resetTime: f64,
+    pub classes: Vec<ClassData>,
+    /// Per-job times of the jobs that left the station: waiting in queue, in service,
+    /// and from arrival to departure.
+    pub waitTimes: Tally,
+    pub serviceTimes: Tally,
+    pub responseTimes: Tally,
+    /// Response times of the jobs that left, for percentiles.
+    pub responseQuantiles: Histogram,
 }
 
 impl StationData {
@@ -29,6 +87,12 @@ impl StationData {
             areaS: 0.0,
             oldclock: 0.0,
             clock: 0.0,
+            resetTime: 0.0,
+            classes: Vec::new(),
+            waitTimes: Tally::new(),
+            serviceTimes: Tally::new(),
+            responseTimes: Tally::new(),
+            responseQuantiles: Histogram::with_gamma(QUANTILE_GAMMA),
         }
     }
 
@@ -42,6 +106,96 @@ impl StationData {
             self.areaN += self.sysClients as f64 * interval;
             self.areaS += (self.sysClients - 1) as f64 * interval;
         }
+        for class in self.classes.iter_mut().filter(|c| c.sysClients > 0) {
+            class.areaN += class.sysClients as f64 * interval;
+        }
+    }
+
+    /// Zeroes the accumulators at the current clock, keeping the state: the jobs in the
+    /// station (in service or waiting) stay and count when they depart.
+    pub fn reset_statistics(&mut self) {
+        self.arrivals = 0;
+        self.completions = 0;
+        self.maxClients = self.sysClients;
+        self.busyTime = 0.0;
+        self.areaN = 0.0;
+        self.areaS = 0.0;
+        self.resetTime = self.clock;
+        self.waitTimes = Tally::new();
+        self.serviceTimes = Tally::new();
+        self.responseTimes = Tally::new();
+        self.responseQuantiles = Histogram::new(QUANTILE_ACCURACY);
+        for class in self.classes.iter_mut() {
+            *class = ClassData {
+                sysClients: class.sysClients,
+                ..ClassData::new()
+            };
+        }
+    }
+
+    /// Length of the observation period since the last reset.
+    pub fn elapsed(&self) -> f64 {
+        self.clock - self.resetTime
+    }
+
+    // Derived metrics over the period since the last reset, up to the last update of the
+    // clock (see Engine::advance to bring it to a given time). The means per job follow
+    // Little's law from the areas, so they also count the time spent so far by the jobs
+    // still in the station; the `Tally` fields hold the means of the jobs that left.
+
+    fn per_time(&self, x: f64) -> f64 {
+        let elapsed = self.elapsed();
+        if elapsed > 0.0 {
+            x / elapsed
+        } else {
+            0.0
+        }
+    }
+
+    fn per_job(&self, x: f64) -> f64 {
+        if self.completions > 0 {
+            x / self.completions as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Fraction of time with at least one job in the station.
+    pub fn utilization(&self) -> f64 {
+        self.per_time(self.busyTime)
+    }
+
+    pub fn arrival_rate(&self) -> f64 {
+        self.per_time(self.arrivals as f64)
+    }
+
+    pub fn throughput(&self) -> f64 {
+        self.per_time(self.completions as f64)
+    }
+
+    /// Time-average number of jobs in the station.
+    pub fn mean_population(&self) -> f64 {
+        self.per_time(self.areaN)
+    }
+
+    /// Time-average number of jobs waiting, all but the one in service.
+    pub fn mean_queue_length(&self) -> f64 {
+        self.per_time(self.areaS)
+    }
+
+    /// Mean population over throughput.
+    pub fn mean_response_time(&self) -> f64 {
+        self.per_job(self.areaN)
+    }
+
+    /// Mean queue length over throughput.
+    pub fn mean_wait(&self) -> f64 {
+        self.per_job(self.areaS)
+    }
+
+    /// Busy time per completion.
+    pub fn mean_service_time(&self) -> f64 {
+        self.per_job(self.busyTime)
     }
 
     pub fn client_arrived(&mut self, arrivalTime: f64){
@@ -59,6 +213,68 @@ impl StationData {
         self.sysClients -= 1;
         self.completions += 1;
     }
+
+    /// Per-class counters, growing the class table on first use.
+    pub fn class_data(&mut self, class: usize) -> &mut ClassData {
+        if class >= self.classes.len() {
+            self.classes.resize(class + 1, ClassData::new());
+        }
+        &mut self.classes[class]
+    }
+
+    pub fn class_arrived(&mut self, class: usize) {
+        let data = self.class_data(class);
+        data.arrivals += 1;
+        data.sysClients += 1;
+    }
+
+    /// Records the departure of `event`, whose `arrivalTime` is the arrival at this station
+    /// and whose `createTime` is the instant its service started.
+    pub fn class_departure(&mut self, event: &Event) {
+        let (clock, reset) = (self.clock, self.resetTime);
+        self.waitTimes.add(event.createTime - event.arrivalTime);
+        self.serviceTimes.add(clock - event.createTime);
+        self.responseTimes.add(clock - event.arrivalTime);
+        self.responseQuantiles.add(clock - event.arrivalTime);
+        let data = self.class_data(event.class);
+        data.sysClients -= 1;
+        data.completions += 1;
+        data.busyTime += clock - event.createTime.max(reset);
+        data.responseTime += clock - event.arrivalTime;
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use crate::Events::DefaultType;
+
+    use super::*;
 
+    #[test]
+    fn test_reset_statistics() {
+        let mut data = StationData::new();
+        for t in [1.0, 2.0, 3.0] {
+            data.update(t);
+            data.client_arrived(t);
+            data.class_arrived(0);
+        }
+        data.update(5.0);
+        data.reset_statistics();
+        assert_eq!((data.arrivals, data.completions, data.sysClients, data.maxClients), (0, 0, 3, 3));
+        assert_eq!((data.areaN, data.busyTime, data.resetTime), (0.0, 0.0, 5.0));
+        assert_eq!(data.classes[0].sysClients, 3);
+        assert_eq!(data.classes[0].arrivals, 0);
+
+        // the job in service since 3.0 departs at 6.0, one unit of it after the reset
+        data.update(6.0);
+        let event = Event::new(DefaultType::DEPARTURE.into(), 3.0, 6.0, 3.0, 1.0, "station".to_string());
+        data.client_departure();
+        data.class_departure(&event);
+        assert_eq!((data.completions, data.sysClients), (1, 2));
+        assert_eq!((data.busyTime, data.areaN, data.elapsed()), (1.0, 3.0, 1.0));
+        assert_eq!(data.classes[0].busyTime, 1.0);
+        assert_eq!(data.classes[0].responseTime, 5.0);
+        assert_eq!((data.waitTimes.mean(), data.serviceTimes.mean(), data.responseTimes.mean()), (2.0, 3.0, 5.0));
+        assert_eq!(data.responseTimes.count(), 1);
+    }
+}
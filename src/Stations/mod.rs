@@ -3,4 +3,7 @@ pub mod FCFSRuler;
 pub mod Station;
 pub mod RoundRobinRuler;
 pub mod ArrivalHandler;
-pub mod DepartureHandler;
\ No newline at end of file
+pub mod DepartureHandler;
+pub mod JobClass;
+pub mod Routing;
+pub mod LoadDependentRuler;
\ No newline at end of file
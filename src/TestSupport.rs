@@ -0,0 +1,41 @@
+/* --------------------------------------------------------------------------
+ * Models shared by the tests: the M/M/1 queue "Source" -> "Server" with
+ * arrival rate 1, its arrivals drawn from stream offset 0 of the plan and
+ * its service times from offset 1.
+ * --------------------------------------------------------------------------
+ */
+
+use crate::{
+    Engines::Engine,
+    Random::{
+        Distributions::Exponential,
+        VarianceReduction::{Purpose, StreamPlan},
+    },
+    Sources::SourceRuler::SourcePolicyManager,
+    Stations::{FCFSRuler::FCFSPolicyManager, Station::Station},
+};
+
+/// Registers the source of the M/M/1 queue on `engine` and returns the server, of
+/// mean service time `service`, left for the caller to register.
+pub fn mm1_server(engine: &mut Engine, plan: &mut StreamPlan, service: f64) -> Station {
+    plan.register("Server", Purpose::Arrivals, 0)
+        .register("Server", Purpose::Services, 1);
+    let mut source = SourcePolicyManager::new("Source", plan.arrivals(Exponential::new(1.0), "Server"), "Server", 0);
+    source.use_engine(engine as *mut Engine);
+    source.start(0.0);
+    let mut station = Station::new("Source");
+    station.set_handler(Box::new(source));
+    engine.register_station(Box::new(station));
+    let mut server = FCFSPolicyManager::new();
+    server.use_engine(engine as *mut Engine);
+    server.set_class_service(0, plan.sampler(Exponential::new(service), "Server", Purpose::Services));
+    let mut station = Station::new("Server");
+    station.set_handler(Box::new(server));
+    station
+}
+
+/// M/M/1 queue with mean service time 0.5: utilization 0.5, mean response time 1.
+pub fn mm1(engine: &mut Engine, plan: &mut StreamPlan) {
+    let server = mm1_server(engine, plan, 0.5);
+    engine.register_station(Box::new(server));
+}
@@ -6,4 +6,7 @@ pub mod Collections;
 pub mod Data;
 pub mod Numerical;
 pub mod Random;
-pub mod Helpers;
\ No newline at end of file
+pub mod Helpers;
+pub mod Sources;
+#[cfg(test)]
+pub(crate) mod TestSupport;